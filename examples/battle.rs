@@ -3,11 +3,11 @@ extern crate mancala;
 
 use clap::{App, Arg};
 use mancala::bout::Bout;
-use mancala::game::{Bowl, GameBuilder, Player};
+use mancala::game::{Bowl, GameBuilder, Header};
 use mancala::strategy::tree::Depth;
 use mancala::strategy::{user, AlphaBeta, First, MinMax, Random, Strategy};
 use rand::thread_rng;
-use std::ops::Neg;
+use std::fs;
 
 fn main() {
     let matches = App::new("Mancala Battle")
@@ -57,13 +57,20 @@ fn main() {
                 .default_value("alphabeta")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("record")
+                .long("record")
+                .value_name("FILE")
+                .help("the file to write the record of the game to")
+                .takes_value(true),
+        )
         .get_matches();
 
     let depth = Depth::Limit(matches.value_of("depth").unwrap().parse().unwrap_or(5));
-    let mut red_strategy =
-        strategy_from_name(matches.value_of("red").unwrap_or("alphabeta"), depth);
-    let mut blue_strategy =
-        strategy_from_name(matches.value_of("blue").unwrap_or("alphabeta"), depth);
+    let red = matches.value_of("red").unwrap_or("alphabeta");
+    let blue = matches.value_of("blue").unwrap_or("alphabeta");
+    let mut red_strategy = strategy_from_name(red, depth);
+    let mut blue_strategy = strategy_from_name(blue, depth);
     let mut bout = Bout::new(
         &mut red_strategy,
         &mut blue_strategy,
@@ -72,13 +79,23 @@ fn main() {
 
     let bowls = matches.value_of("bowls").unwrap().parse().unwrap_or(6);
     let stones = matches.value_of("stones").unwrap().parse().unwrap_or(4);
-    let game = GameBuilder::new().bowls(bowls).stones(stones).build();
+    let game = GameBuilder::new()
+        .bowls(bowls)
+        .stones(stones)
+        .build()
+        .expect("a playable game");
     let result = bout.start(game).expect("a finished game with score");
-    let mut score = result.score().expect("a defined score");
-    if result.turn() != Player::Red {
-        score = score.neg();
+    let outcome = result.outcome().expect("a defined outcome");
+    println!("{}", outcome);
+
+    if let Some(file) = matches.value_of("record") {
+        let header = Header {
+            red: red.to_string(),
+            blue: blue.to_string(),
+            ..Header::default()
+        };
+        fs::write(file, result.record(&header).to_string()).expect("record to be written");
     }
-    println!("{:?}", score);
 }
 
 fn strategy_from_name(name: &str, depth: Depth) -> Box<dyn Strategy> {
@@ -0,0 +1,20 @@
+extern crate mancala;
+
+use mancala::game::Position;
+use mancala::strategy::tree::ordering::InOrder;
+use mancala::strategy::tree::{AlphaBeta, Depth, DepthLimitedSearch};
+
+fn main() {
+    for depth in 4..10 {
+        let position = Position::new(6, 4);
+        let search_depth = Depth::Limit(depth);
+
+        let mut in_order = AlphaBeta::strategy().ordered_by(InOrder {}).build();
+        in_order.search(&position, &search_depth);
+        let mut prioritized = AlphaBeta::strategy().build();
+        prioritized.search(&position, &search_depth);
+
+        println!("depth: {} in order    {}", depth, in_order.statistics);
+        println!("depth: {} prioritized {}", depth, prioritized.statistics);
+    }
+}
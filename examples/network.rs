@@ -0,0 +1,79 @@
+extern crate clap;
+extern crate mancala;
+
+use clap::{App, Arg};
+use mancala::bout::Bout;
+use mancala::game::GameBuilder;
+use mancala::learning::network::{Adam, NetworkBuilder, Sample};
+use mancala::strategy::tree::Depth;
+use mancala::strategy::AlphaBeta;
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+
+fn main() {
+    let matches = App::new("Mancala Network")
+        .version("1.0")
+        .author("Daan van Berkel <daan.v.berkel.1980@gmail.com>")
+        .about("Train a value and policy network on games of alpha beta against itself")
+        .arg(
+            Arg::with_name("games")
+                .short("g")
+                .long("games")
+                .value_name("NUMBER")
+                .help("the number of games to learn from")
+                .default_value("200")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("epochs")
+                .short("e")
+                .long("epochs")
+                .value_name("NUMBER")
+                .help("the number of passes over the samples")
+                .default_value("20")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .value_name("FILE")
+                .help("the file to write the parameters to")
+                .default_value("network.txt")
+                .takes_value(true),
+        )
+        .get_matches();
+
+    let games: usize = matches.value_of("games").unwrap().parse().unwrap_or(200);
+    let epochs: usize = matches.value_of("epochs").unwrap().parse().unwrap_or(20);
+    let mut rng = thread_rng();
+
+    let mut samples = vec![];
+    for _ in 0..games {
+        let mut game = GameBuilder::new().build().expect("a playable game");
+        for _ in 0..2 {
+            let bowl = *game.options().choose(&mut rng).expect("an option");
+            game.play(bowl).expect("option to be playable");
+        }
+        let mut red = AlphaBeta::strategy().limited_to(Depth::Limit(4)).build();
+        let mut blue = AlphaBeta::strategy().limited_to(Depth::Limit(4)).build();
+        let game = Bout::new(&mut red, &mut blue, &|_| {})
+            .start(game)
+            .expect("a finished game");
+        samples.extend(Sample::from_game(&game));
+    }
+    println!("{} samples from {} games", samples.len(), games);
+
+    let mut network = NetworkBuilder::new().hidden(&[64, 32]).build();
+    let mut optimizer = Adam::new(0.001);
+    for epoch in 1..=epochs {
+        samples.shuffle(&mut rng);
+        for batch in samples.chunks(32) {
+            network.train(batch, &mut optimizer);
+        }
+        println!("epoch {:>3}: {}", epoch, network.loss(&samples));
+    }
+
+    let file = matches.value_of("output").unwrap();
+    network.save(file).expect("parameters to be saved");
+}
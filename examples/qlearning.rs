@@ -0,0 +1,28 @@
+extern crate mancala;
+
+use mancala::game::Position;
+use mancala::learning::tabular::{QLearning, Schedule, Update};
+
+fn main() {
+    let start = Position::new(3, 2);
+    for update in &[Update::QLearning, Update::Sarsa] {
+        let mut agent = QLearning::strategy()
+            .updating(*update)
+            .exploring(Schedule::Linear {
+                start: 1.0,
+                end: 0.0,
+                episodes: 200_000,
+            })
+            .build();
+        for _ in 0..10 {
+            agent.train(&start, 25_000);
+            println!(
+                "{:?} after {} episodes, {} positions: {}",
+                update,
+                agent.episodes(),
+                agent.positions(),
+                agent.convergence(&start)
+            );
+        }
+    }
+}
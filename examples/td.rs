@@ -0,0 +1,86 @@
+extern crate clap;
+extern crate mancala;
+
+use clap::{App, Arg};
+use mancala::bout::Bout;
+use mancala::game::{GameBuilder, Position};
+use mancala::learning::TemporalDifference;
+use mancala::strategy::tree::Depth;
+use mancala::strategy::{AlphaBeta, Strategy};
+
+fn main() {
+    let matches = App::new("Mancala TD")
+        .version("1.0")
+        .author("Daan van Berkel <daan.v.berkel.1980@gmail.com>")
+        .about("Train a TD(λ) agent by self-play")
+        .arg(
+            Arg::with_name("episodes")
+                .short("e")
+                .long("episodes")
+                .value_name("NUMBER")
+                .help("the number of episodes to train for")
+                .default_value("10000")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("start")
+                .long("start")
+                .value_name("FILE")
+                .help("the parameters to continue training from")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .value_name("FILE")
+                .help("the file to write the parameters to")
+                .default_value("td.txt")
+                .takes_value(true),
+        )
+        .get_matches();
+
+    let episodes: usize = matches
+        .value_of("episodes")
+        .unwrap()
+        .parse()
+        .unwrap_or(10000);
+    let mut agent = match matches.value_of("start") {
+        Some(file) => TemporalDifference::load(file).expect("parameters to be loaded"),
+        None => TemporalDifference::strategy().build(),
+    };
+
+    let start = Position::new(6, 4);
+    let rounds = 10;
+    for round in 1..=rounds {
+        let score = agent.train(&start, episodes / rounds);
+        let (red, blue) = (margin(&mut agent, true), margin(&mut agent, false));
+        println!(
+            "round {:>2}: self-play {:+.2}, against delta as red {:+}, as blue {:+}",
+            round, score, red, blue
+        );
+    }
+
+    let file = matches.value_of("output").unwrap();
+    agent.save(file).expect("parameters to be saved");
+}
+
+/// The margin of the agent against a one-ply search with the delta heuristic.
+fn margin(agent: &mut TemporalDifference, red: bool) -> i16 {
+    let mut opponent = AlphaBeta::strategy().limited_to(Depth::Limit(1)).build();
+    let (red_strategy, blue_strategy): (&mut dyn Strategy, &mut dyn Strategy) = if red {
+        (agent, &mut opponent)
+    } else {
+        (&mut opponent, agent)
+    };
+    let game = GameBuilder::new().build().expect("a playable game");
+    let result = Bout::new(red_strategy, blue_strategy, &|_| {})
+        .start(game)
+        .expect("a finished game");
+    let margin = result.outcome().expect("a defined outcome").margin();
+    if red {
+        margin
+    } else {
+        -margin
+    }
+}
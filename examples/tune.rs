@@ -0,0 +1,89 @@
+extern crate clap;
+extern crate mancala;
+
+use clap::{App, Arg};
+use mancala::strategy::heuristic::{
+    about_to_be_captured, capture_threats, delta, extra_turns, mobility, stones_on_side, tempo,
+    Weighted,
+};
+use mancala::strategy::tree::Depth;
+use mancala::tuning::{Spsa, Weights};
+use std::fs;
+
+fn main() {
+    let matches = App::new("Mancala Tune")
+        .version("1.0")
+        .author("Daan van Berkel <daan.v.berkel.1980@gmail.com>")
+        .about("Tune the weights of the positional heuristics by self-play")
+        .arg(
+            Arg::with_name("iterations")
+                .short("i")
+                .long("iterations")
+                .value_name("NUMBER")
+                .help("the number of iterations")
+                .default_value("100")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("depth")
+                .short("d")
+                .long("depth")
+                .value_name("NUMBER")
+                .help("the search depth of the strategies")
+                .default_value("3")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("start")
+                .long("start")
+                .value_name("FILE")
+                .help("the weights file to start from")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .value_name("FILE")
+                .help("the file to write the tuned weights to")
+                .default_value("weights.txt")
+                .takes_value(true),
+        )
+        .get_matches();
+
+    let iterations = matches
+        .value_of("iterations")
+        .unwrap()
+        .parse()
+        .unwrap_or(100);
+    let depth = Depth::Limit(matches.value_of("depth").unwrap().parse().unwrap_or(3));
+    let mut builder = Spsa::tuning(features)
+        .iterations(iterations)
+        .limited_to(depth);
+    if let Some(file) = matches.value_of("start") {
+        let weights: Weights = fs::read_to_string(file)
+            .expect("weights file to be read")
+            .parse()
+            .expect("weights file to be valid");
+        builder = builder.starting_at(weights);
+    }
+    let mut spsa = builder.build().expect("a playable game");
+
+    for _ in 0..iterations {
+        println!("{}", spsa.step());
+    }
+
+    let file = matches.value_of("output").unwrap();
+    fs::write(file, spsa.weights().to_string()).expect("weights to be written");
+}
+
+fn features() -> Weighted {
+    Weighted::new()
+        .with(1.0, delta())
+        .with(0.0, stones_on_side())
+        .with(0.0, mobility())
+        .with(0.0, extra_turns())
+        .with(0.0, capture_threats())
+        .with(0.0, about_to_be_captured())
+        .with(0.0, tempo())
+}
@@ -0,0 +1,74 @@
+extern crate clap;
+extern crate mancala;
+
+use clap::{App, Arg};
+use mancala::game::{GameBuilder, Stones};
+use mancala::learning::zero::SelfPlay;
+
+fn main() {
+    let matches = App::new("Mancala Zero")
+        .version("1.0")
+        .author("Daan van Berkel <daan.v.berkel.1980@gmail.com>")
+        .about("Train a value and policy network by tree search guided self-play")
+        .arg(
+            Arg::with_name("generations")
+                .short("g")
+                .long("generations")
+                .value_name("NUMBER")
+                .help("the number of generations to train")
+                .default_value("10")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("bowls")
+                .short("b")
+                .long("bowls")
+                .value_name("NUMBER")
+                .help("the number of bowls per player")
+                .default_value("4")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("stones")
+                .short("s")
+                .long("stones")
+                .value_name("NUMBER")
+                .help("the number of stones per bowl")
+                .default_value("3")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("checkpoints")
+                .short("c")
+                .long("checkpoints")
+                .value_name("DIRECTORY")
+                .help("the directory to save checkpoints to, and resume from")
+                .default_value("checkpoints")
+                .takes_value(true),
+        )
+        .get_matches();
+
+    let generations: usize = matches
+        .value_of("generations")
+        .unwrap()
+        .parse()
+        .unwrap_or(10);
+    let bowls: usize = matches.value_of("bowls").unwrap().parse().unwrap_or(4);
+    let stones: Stones = matches.value_of("stones").unwrap().parse().unwrap_or(3);
+    let directory = matches.value_of("checkpoints").unwrap();
+
+    let mut pipeline = SelfPlay::training()
+        .on(GameBuilder::new().bowls(bowls).stones(stones))
+        .simulations(50)
+        .checkpoints(directory)
+        .build()
+        .expect("a playable game");
+    let resumed = pipeline.resume().expect("checkpoints to be readable");
+    if resumed > 0 {
+        println!("resuming from generation {}", resumed);
+    }
+    for _ in 0..generations {
+        let generation = pipeline.generation().expect("checkpoint to be saved");
+        println!("{}", generation);
+    }
+}
@@ -3,10 +3,47 @@ extern crate mancala;
 
 use clap::{App, Arg};
 use mancala::bout::Bout;
-use mancala::game::{Bowl, GameBuilder, Player};
-use mancala::strategy::tree::Depth;
-use mancala::strategy::{user, AlphaBeta};
-use std::ops::Neg;
+use mancala::game::{Bowl, GameBuilder, Position};
+use mancala::strategy::tree::{Depth, IterativeDeepeningSearch};
+use mancala::strategy::{user, AlphaBeta, Analyse, Strategy};
+use std::time::Duration;
+
+/// Show the value of every option before asking for a play.
+struct Hinted<S, A>
+where
+    S: Strategy,
+    A: Analyse,
+{
+    strategy: S,
+    analyst: A,
+    depth: Depth,
+}
+
+impl<S, A> Strategy for Hinted<S, A>
+where
+    S: Strategy,
+    A: Analyse,
+{
+    fn play(&mut self, position: &Position) -> Option<Bowl> {
+        let analysis = self.analyst.analyse(position, &self.depth, 1);
+        println!("hints: {}", analysis);
+        self.strategy.play(position)
+    }
+}
+
+/// The longest the computer is allowed to think per play, in seconds.
+const MAX_THINKING_TIME: f64 = 3600.0;
+
+/// Accept thinking times between zero and `MAX_THINKING_TIME` seconds.
+fn thinking_time(seconds: String) -> Result<(), String> {
+    match seconds.parse::<f64>() {
+        Ok(seconds) if (0.0..=MAX_THINKING_TIME).contains(&seconds) => Ok(()),
+        _ => Err(format!(
+            "expected a number of seconds between 0 and {}",
+            MAX_THINKING_TIME
+        )),
+    }
+}
 
 fn main() {
     let matches = App::new("Play Mancala")
@@ -40,6 +77,20 @@ fn main() {
                 .default_value("5")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("think")
+                .short("t")
+                .long("think")
+                .value_name("SECONDS")
+                .help("the time the computer thinks per play, overrides the depth")
+                .validator(thinking_time)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("hints")
+                .long("hints")
+                .help("show the value of every option before you play"),
+        )
         .arg(
             Arg::with_name("first")
                 .short("f")
@@ -51,11 +102,31 @@ fn main() {
         )
         .get_matches();
 
-    let mut red_strategy = user();
     let depth = matches.value_of("depth").unwrap().parse().unwrap_or(5);
-    let mut blue_strategy = AlphaBeta::strategy()
-        .limited_to(Depth::Limit(depth))
-        .build();
+    let mut red_strategy: Box<dyn Strategy> = if matches.is_present("hints") {
+        Box::new(Hinted {
+            strategy: user(),
+            analyst: AlphaBeta::strategy().build(),
+            depth: Depth::Limit(depth),
+        })
+    } else {
+        Box::new(user())
+    };
+    let mut blue_strategy: Box<dyn Strategy> = match matches.value_of("think") {
+        Some(seconds) => {
+            let seconds = seconds.parse().expect("a validated thinking time");
+            Box::new(
+                IterativeDeepeningSearch::strategy(AlphaBeta::strategy().build())
+                    .thinking_for(Duration::from_secs_f64(seconds))
+                    .build(),
+            )
+        }
+        None => Box::new(
+            AlphaBeta::strategy()
+                .limited_to(Depth::Limit(depth))
+                .build(),
+        ),
+    };
     let go_first = matches.value_of("first").unwrap().parse().unwrap_or(true);
     let mut bout = if go_first {
         Bout::new(
@@ -73,11 +144,12 @@ fn main() {
 
     let bowls = matches.value_of("bowls").unwrap().parse().unwrap_or(6);
     let stones = matches.value_of("stones").unwrap().parse().unwrap_or(4);
-    let game = GameBuilder::new().bowls(bowls).stones(stones).build();
+    let game = GameBuilder::new()
+        .bowls(bowls)
+        .stones(stones)
+        .build()
+        .expect("a playable game");
     let result = bout.start(game).expect("a finished game with score");
-    let mut score = result.score().expect("a defined score");
-    if result.turn() != Player::Red {
-        score = score.neg();
-    }
-    println!("{:?}", score);
+    let outcome = result.outcome().expect("a defined outcome");
+    println!("{}", outcome);
 }
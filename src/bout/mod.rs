@@ -74,3 +74,60 @@ impl<'a> Bout<'a> {
         result.map(|_| game)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameBuilder, Position};
+    use crate::strategy::First;
+
+    struct Stubborn {
+        bowl: Bowl,
+    }
+
+    impl Strategy for Stubborn {
+        fn play(&mut self, _position: &Position) -> Option<Bowl> {
+            Some(self.bowl)
+        }
+    }
+
+    #[test]
+    fn playing_the_bowl_of_the_opponent_is_reported() {
+        let mut red_strategy = Stubborn { bowl: 4 };
+        let mut blue_strategy = First::new();
+        let mut bout = Bout::new(&mut red_strategy, &mut blue_strategy, &|_| {});
+
+        let result = bout.start(
+            GameBuilder::new()
+                .bowls(3)
+                .stones(2)
+                .build()
+                .expect("a playable game"),
+        );
+
+        assert!(matches!(
+            result,
+            Err(Problem::IllegalPlay(Player::Red, FoulPlay::OpponentsBowl))
+        ));
+    }
+
+    #[test]
+    fn playing_a_bowl_that_is_not_on_the_board_is_reported() {
+        let mut red_strategy = Stubborn { bowl: 6 };
+        let mut blue_strategy = First::new();
+        let mut bout = Bout::new(&mut red_strategy, &mut blue_strategy, &|_| {});
+
+        let result = bout.start(
+            GameBuilder::new()
+                .bowls(3)
+                .stones(2)
+                .build()
+                .expect("a playable game"),
+        );
+
+        assert!(matches!(
+            result,
+            Err(Problem::IllegalPlay(Player::Red, FoulPlay::BowlOutOfRange))
+        ));
+    }
+}
@@ -1,7 +1,7 @@
 //! Mancala Game related definitions
 //!
 //! [Mancala](https://en.wikipedia.org/wiki/Mancala) is a game with many variants.
-//! Here we focus on one variant, but we allow different number of bowls.
+//! The rules of a variant are described by a `Ruleset`, the default being `Kalah`. We allow different number of bowls.
 //!
 //! The code below shows how one would build a standard mancala game.
 //!
@@ -11,39 +11,52 @@
 //!   GameBuilder::new()
 //!     .bowls(6)
 //!     .stones(4)
-//!     .build();
+//!     .build()
+//!     .expect("a playable game");
 //! ```
 
-use std::fmt::{self, Display, Formatter};
+pub mod notation;
+pub mod record;
+pub mod ruleset;
+mod zobrist;
+
+pub use self::notation::{MalformedNotation, Notation};
+pub use self::record::{BadRecord, Header, Record};
+pub use self::ruleset::{Capture, Captures, Kalah, Oware, Pit, Ruleset, Sweep, Variant};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
 
 /// Representation of a Bowl
 pub type Bowl = usize;
 
 /// Representation of a number of stones in a bowl
-pub type Stones = u8;
+pub type Stones = u16;
 
 /// Score a finished game;
-pub type Score = i8;
+pub type Score = i16;
 
 /// GameBuilder is used to create a Mancala game.
+#[derive(Debug, Copy, Clone)]
 pub struct GameBuilder {
-    bowls: u8,
+    bowls: usize,
     stones: Stones,
+    variant: Variant,
 }
 
 impl GameBuilder {
     /// Creates a new GameBuilder
     ///
-    /// The default number of bowls is 6 and the default number of stones per bowl is 4.
+    /// The default number of bowls is 6 and the default number of stones per bowl is 4. The default rules are `Kalah`.
     pub fn new() -> Self {
         GameBuilder {
             bowls: 6,
             stones: 4,
+            variant: Variant::default(),
         }
     }
 
     /// Sets the number of bowls for this GameBuilder
-    pub fn bowls(self, bowls: u8) -> Self {
+    pub fn bowls(self, bowls: usize) -> Self {
         GameBuilder { bowls, ..self }
     }
 
@@ -52,13 +65,68 @@ impl GameBuilder {
         GameBuilder { stones, ..self }
     }
 
+    /// Sets the rules for this GameBuilder
+    pub fn rules<V>(self, rules: V) -> Self
+    where
+        V: Into<Variant>,
+    {
+        GameBuilder {
+            variant: rules.into(),
+            ..self
+        }
+    }
+
+    /// Sets what is captured when playing `Kalah`
+    ///
+    /// Has no effect for other variants.
+    pub fn capture(self, capture: Capture) -> Self {
+        self.kalah(|kalah| kalah.capturing(capture))
+    }
+
+    /// Sets if a capture happens when the opposite bowl is empty, when playing `Kalah`
+    ///
+    /// Has no effect for other variants.
+    pub fn capture_from_empty(self, capture_from_empty: bool) -> Self {
+        self.kalah(|kalah| kalah.capturing_from_empty(capture_from_empty))
+    }
+
+    /// Sets where the stones left at the end of the game go, when playing `Kalah`
+    ///
+    /// Has no effect for other variants.
+    pub fn sweep(self, sweep: Sweep) -> Self {
+        self.kalah(|kalah| kalah.sweeping_to(sweep))
+    }
+
+    fn kalah<F>(self, change: F) -> Self
+    where
+        F: FnOnce(Kalah) -> Kalah,
+    {
+        match self.variant {
+            Variant::Kalah(kalah) => self.rules(change(kalah)),
+            _ => self,
+        }
+    }
+
     /// Creates a Game with the required number of bowls and stones per bowl
-    pub fn build(self) -> Game {
-        let current = Position::new(self.bowls, self.stones);
-        Game {
-            current,
-            history: vec![],
+    ///
+    /// Fails if there are more than `MAX_BOWLS` bowls, or if the total number of stones does not fit in a `Score`.
+    pub fn build(self) -> Result<Game, BadSetup> {
+        if self.bowls > MAX_BOWLS {
+            return Err(BadSetup::TooManyBowls);
         }
+        let total = (2 * self.bowls)
+            .checked_mul(self.stones as usize)
+            .ok_or(BadSetup::TooManyStones)?;
+        if total > Score::MAX as usize {
+            return Err(BadSetup::TooManyStones);
+        }
+        let start = Position::new(self.bowls, self.stones).with_rules(self.variant);
+        Ok(Game {
+            start,
+            current: start,
+            history: vec![],
+            undone: vec![],
+        })
     }
 }
 
@@ -68,14 +136,25 @@ impl Default for GameBuilder {
     }
 }
 
+/// Discriminates between all the ways a game can not be set up.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum BadSetup {
+    /// There are more than `MAX_BOWLS` bowls.
+    TooManyBowls,
+    /// The total number of stones does not fit in a `Score`.
+    TooManyStones,
+}
+
 /// Game is an sequence of Positions.
 ///
 /// A Game is created with a GameBuilder.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Game {
+    start: Position,
     /// The current position of this game
     pub current: Position,
     history: Vec<(Player, Bowl)>,
+    undone: Vec<(Player, Bowl)>,
 }
 
 impl Game {
@@ -91,16 +170,56 @@ impl Game {
 
     /// Play a certain bowl.
     ///
-    /// Fails if the bowl does not contain any stones.
+    /// Fails if the play is not allowed. Plays that were undone can no longer be redone.
     pub fn play(&mut self, bowl: Bowl) -> Result<(), FoulPlay> {
-        match self.current.play(bowl) {
-            Some(position) => {
-                self.history.push((self.current.player, bowl));
-                self.current = position;
-                Ok(())
-            }
+        let position = self.current.play(bowl)?;
+        self.history.push((self.current.player, bowl));
+        self.undone.clear();
+        self.current = position;
+        Ok(())
+    }
+
+    /// Take back the last play.
+    ///
+    /// Returns the play that was taken back, if any.
+    pub fn undo(&mut self) -> Option<(Player, Bowl)> {
+        let play = self.history.pop()?;
+        self.undone.push(play);
+        self.current = self
+            .positions()
+            .last()
+            .expect("at least the start position");
+        Some(play)
+    }
+
+    /// Play the last play that was taken back again.
+    ///
+    /// Returns the play that was played again, if any.
+    pub fn redo(&mut self) -> Option<(Player, Bowl)> {
+        let play = self.undone.pop()?;
+        self.current = self
+            .current
+            .play(play.1)
+            .expect("undone play to be playable");
+        self.history.push(play);
+        Some(play)
+    }
 
-            None => Err(FoulPlay::NoStonesInBowl),
+    /// The position this game started from
+    pub fn start(&self) -> &Position {
+        &self.start
+    }
+
+    /// The plays made in this game, in order
+    pub fn history(&self) -> &[(Player, Bowl)] {
+        &self.history
+    }
+
+    /// Iterate over every position of this game, from the start up to and including the current position.
+    pub fn positions(&self) -> Positions<'_> {
+        Positions {
+            next: Some(self.start),
+            plays: self.history.iter(),
         }
     }
 
@@ -111,30 +230,120 @@ impl Game {
         self.current.score()
     }
 
+    /// Determine the outcome of a game.
+    ///
+    /// None if the game is not finished
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.current.outcome()
+    }
+
     /// Return which players turn it is
     pub fn turn(&self) -> Player {
         self.current.turn()
     }
 }
 
+/// The outcome of a finished game.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Outcome {
+    margin: Score,
+}
+
+impl Outcome {
+    /// The player that won, none if the game is a draw.
+    pub fn winner(&self) -> Option<Player> {
+        match self.margin {
+            margin if margin > 0 => Some(Player::Red),
+            margin if margin < 0 => Some(Player::Blue),
+            _ => None,
+        }
+    }
+
+    /// Determine if the game is a draw.
+    pub fn is_draw(&self) -> bool {
+        self.margin == 0
+    }
+
+    /// The score of the game for `Red`.
+    pub fn margin(&self) -> Score {
+        self.margin
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.winner() {
+            Some(player) => write!(f, "{:?} wins by {}", player, self.margin.abs()),
+            None => write!(f, "draw"),
+        }
+    }
+}
+
+/// Iterator over the positions of a game, rebuilt from its start.
+pub struct Positions<'a> {
+    next: Option<Position>,
+    plays: std::slice::Iter<'a, (Player, Bowl)>,
+}
+
+impl<'a> Iterator for Positions<'a> {
+    type Item = Position;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let position = self.next?;
+        self.next = self.plays.next().map(|(_, bowl)| {
+            position
+                .play(*bowl)
+                .expect("history to consist of legal plays")
+        });
+        Some(position)
+    }
+}
+
 /// Discriminates between all the ways a play can go wrong.
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub enum FoulPlay {
     /// Playing a bowl when there are no stones in the bowl, is foul play.
     NoStonesInBowl,
+    /// Playing a bowl that is not on the board, is foul play.
+    BowlOutOfRange,
+    /// Playing a bowl of the opponent, is foul play.
+    OpponentsBowl,
+    /// Playing when the game is finished, is foul play.
+    GameAlreadyFinished,
+    /// Playing a bowl the rules do not allow, is foul play.
+    ForbiddenByRules,
 }
 
+/// The maximum number of bowls each player can have.
+pub const MAX_BOWLS: usize = 255;
+
 /// Position is a instance of the board.
-#[derive(Debug, PartialEq)]
+///
+/// The board is stored from the perspective of `Red`, in a fixed size array. So a position is cheap to copy, and
+/// playing a bowl does not allocate.
+///
+/// A position keeps a Zobrist hash of its bowls, stores, side to move and variant up to date, see `hash_key`.
+#[derive(PartialEq, Eq, Copy, Clone)]
 pub struct Position {
     player: Player,
     size: usize,
     capture: [Stones; 2],
-    bowls: Vec<Stones>,
+    bowls: [Stones; 2 * MAX_BOWLS],
+    variant: Variant,
+    hash: u64,
+}
+
+/// Remembers what is needed to take back a play made with `Position::make`.
+#[derive(Debug, Copy, Clone)]
+pub struct Undo {
+    player: Player,
+    capture: [Stones; 2],
+    bowls: [Stones; 2 * MAX_BOWLS],
+    hash: u64,
 }
 
 /// The names for the player.
-#[derive(Debug, PartialEq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 pub enum Player {
     /// The starting player
     Red,
@@ -154,84 +363,209 @@ impl Player {
 
 impl Position {
     /// Create a position with a number of bowls and a number of stones per bowl.
-    pub fn new(bowls: u8, stones: Stones) -> Self {
-        let size = bowls as usize;
-        let bowls = vec![stones; 2 * size];
-        Position {
-            player: Player::Red,
+    ///
+    /// Panics if there are more than `MAX_BOWLS` bowls.
+    pub fn new(bowls: usize, stones: Stones) -> Self {
+        assert!(bowls <= MAX_BOWLS, "at most {} bowls", MAX_BOWLS);
+        let size = bowls;
+        let mut bowls = [0; 2 * MAX_BOWLS];
+        bowls[0..2 * size].iter_mut().for_each(|s| *s = stones);
+        Position::absolute(Player::Red, size, [0, 0], bowls)
+    }
+
+    fn absolute(
+        player: Player,
+        size: usize,
+        capture: [Stones; 2],
+        bowls: [Stones; 2 * MAX_BOWLS],
+    ) -> Self {
+        let mut position = Position {
+            player,
             size,
-            capture: [0, 0],
+            capture,
             bowls,
+            variant: Variant::default(),
+            hash: 0,
+        };
+        position.hash = position.rehash();
+        position
+    }
+
+    fn relative(player: Player, capture: [Stones; 2], bowls: &[Stones]) -> Self {
+        let mut position = Position::absolute(player, bowls.len() / 2, [0, 0], [0; 2 * MAX_BOWLS]);
+        for (bowl, stones) in bowls.iter().enumerate() {
+            position.set(Pit::Bowl(bowl), *stones);
+        }
+        position.set(Pit::Store, capture[0]);
+        position.set(Pit::OpponentStore, capture[1]);
+        position
+    }
+
+    fn rehash(&self) -> u64 {
+        let bowls = (0..2 * self.size).map(|index| zobrist::pit(index, self.bowls[index]));
+        let stores = (0..2).map(|index| zobrist::pit(2 * MAX_BOWLS + index, self.capture[index]));
+        let side = match self.player {
+            Player::Red => 0,
+            Player::Blue => zobrist::side(),
+        };
+        let variant = zobrist::variant(&self.variant);
+        bowls
+            .chain(stores)
+            .fold(side ^ variant, |hash, key| hash ^ key)
+    }
+
+    /// A Zobrist hash of the bowls, the stores, the side to move and the variant.
+    ///
+    /// It is kept up to date while playing, so it is cheap to ask for. Positions that only differ in the rules they
+    /// are played under have different hash keys, so searches under different rules do not share results.
+    pub fn hash_key(&self) -> u64 {
+        self.hash
+    }
+
+    /// Play this position under different rules.
+    pub fn with_rules<V>(self, rules: V) -> Self
+    where
+        V: Into<Variant>,
+    {
+        let variant = rules.into();
+        Position {
+            variant,
+            hash: self.hash ^ zobrist::variant(&self.variant) ^ zobrist::variant(&variant),
+            ..self
+        }
+    }
+
+    /// The variant this position is played under.
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// The number of bowls each player has.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The number of stones in a pit, seen from the current player.
+    pub fn stones(&self, pit: Pit) -> Stones {
+        match pit {
+            Pit::Bowl(bowl) => self.bowls[self.index(bowl)],
+            Pit::Store => self.capture[store_index(self.player)],
+            Pit::OpponentStore => self.capture[store_index(self.player.other())],
+        }
+    }
+
+    fn set(&mut self, pit: Pit, stones: Stones) {
+        let (index, slot) = match pit {
+            Pit::Bowl(bowl) => {
+                let index = self.index(bowl);
+                (index, &mut self.bowls[index])
+            }
+            Pit::Store => {
+                let index = store_index(self.player);
+                (2 * MAX_BOWLS + index, &mut self.capture[index])
+            }
+            Pit::OpponentStore => {
+                let index = store_index(self.player.other());
+                (2 * MAX_BOWLS + index, &mut self.capture[index])
+            }
+        };
+        self.hash ^= zobrist::pit(index, *slot) ^ zobrist::pit(index, stones);
+        *slot = stones;
+    }
+
+    fn index(&self, bowl: Bowl) -> usize {
+        match self.player {
+            Player::Red => bowl,
+            Player::Blue => (bowl + self.size) % (2 * self.size),
         }
     }
 
     /// Determine which bowls are playable.
     pub fn options(&self) -> Vec<Bowl> {
-        self.bowls[0..self.size]
-            .iter()
-            .cloned()
-            .enumerate()
-            .filter_map(|(bowl, stones)| if stones > 0 { Some(bowl) } else { None })
+        let ruleset = self.variant.ruleset();
+        (0..self.size)
+            .filter(|bowl| ruleset.playable(self, *bowl))
             .collect()
     }
 
+    /// Determine if any bowl is playable.
+    ///
+    /// Unlike `options`, this does not allocate.
+    pub fn has_options(&self) -> bool {
+        let ruleset = self.variant.ruleset();
+        (0..self.size).any(|bowl| ruleset.playable(self, bowl))
+    }
+
     /// Play a certain bowl.
     ///
-    /// If the bowl is empty, returns nothing.
-    pub fn play(&self, bowl: Bowl) -> Option<Self> {
-        if self.bowls[bowl] > 0 {
-            Some(self.sow(bowl))
+    /// Fails if the play is not allowed.
+    pub fn play(&self, bowl: Bowl) -> Result<Self, FoulPlay> {
+        let mut position = *self;
+        position.make(bowl)?;
+        Ok(position)
+    }
+
+    /// Play a certain bowl in place.
+    ///
+    /// The returned `Undo` can be handed to `unmake` to take the play back. Fails if the play is not allowed, leaving
+    /// the position untouched.
+    pub fn make(&mut self, bowl: Bowl) -> Result<Undo, FoulPlay> {
+        if self.finished() {
+            Err(FoulPlay::GameAlreadyFinished)
+        } else if bowl >= 2 * self.size {
+            Err(FoulPlay::BowlOutOfRange)
+        } else if bowl >= self.size {
+            Err(FoulPlay::OpponentsBowl)
+        } else if self.stones(Pit::Bowl(bowl)) == 0 {
+            Err(FoulPlay::NoStonesInBowl)
+        } else if !self.variant.ruleset().playable(self, bowl) {
+            Err(FoulPlay::ForbiddenByRules)
         } else {
-            None
+            let undo = Undo {
+                player: self.player,
+                capture: self.capture,
+                bowls: self.bowls,
+                hash: self.hash,
+            };
+            self.sow(bowl);
+            Ok(undo)
         }
     }
 
-    fn sow(&self, bowl: Bowl) -> Self {
-        let mut player = self.player;
-        let mut bowls = self.bowls.clone();
-        let stones = bowls[bowl];
-        bowls[bowl] = 0;
-        let tour = 2 * self.size + 1;
-        let all_gain = stones as usize / tour;
-        bowls = bowls
-            .iter()
-            .map(|s| (*s as usize + all_gain) as Stones)
-            .collect();
-        let extra = stones as usize % tour;
-        let final_index = bowl + extra;
-        (1..=(if final_index >= self.size {
-            extra - 1
-        } else {
-            extra
-        }))
-            .for_each(|i| bowls[(bowl + i) % (2 * self.size)] += 1);
-        let mut captured = 0;
-        if final_index < self.size && bowls[final_index] == 1 {
-            let index = 2 * self.size - 1 - final_index;
-            captured = bowls[index] as usize;
-            bowls[index] = 0;
+    /// Take back a play made with `make`.
+    pub fn unmake(&mut self, undo: Undo) {
+        self.player = undo.player;
+        self.capture = undo.capture;
+        self.bowls = undo.bowls;
+        self.hash = undo.hash;
+    }
+
+    fn sow(&mut self, bowl: Bowl) {
+        let variant = self.variant;
+        let ruleset = variant.ruleset();
+        let stones = self.stones(Pit::Bowl(bowl));
+        self.set(Pit::Bowl(bowl), 0);
+        let mut pit = Pit::Bowl(bowl);
+        for _ in 0..stones {
+            pit = ruleset.next(self.size, bowl, pit);
+            self.set(pit, self.stones(pit) + 1);
         }
-        let capture_gain = all_gain + captured + if final_index >= self.size { 1 } else { 0 };
-        let mut capture = [self.capture[0] + capture_gain as Stones, self.capture[1]];
-        let change_player = final_index != self.size;
-        if change_player {
-            player = self.player.other();
-            capture = [capture[1], capture[0]];
-            bowls.rotate_left(self.size);
+        for captured in ruleset.capture(self, pit).bowls() {
+            let stones = self.stones(Pit::Bowl(captured));
+            self.set(Pit::Bowl(captured), 0);
+            self.set(Pit::Store, self.stones(Pit::Store) + stones);
         }
-        Position {
-            player,
-            size: self.size,
-            capture,
-            bowls,
+        if !ruleset.extra_turn(self, pit) {
+            self.player = self.player.other();
+            self.hash ^= zobrist::side();
         }
     }
 
     /// Determine if a position is finished.
     ///
-    /// A position is finished when the current play can't make any plays
+    /// When a position is finished is up to the rules it is played under.
     pub fn finished(&self) -> bool {
-        self.bowls[0..self.size].iter().all(|&stones| stones == 0)
+        self.variant.ruleset().finished(self)
     }
 
     /// Which player is allowed to make a play
@@ -239,26 +573,64 @@ impl Position {
         self.player
     }
 
+    /// The same board, with the other player to make a play.
+    ///
+    /// This is not a legal play, but it allows to look at a position from the perspective of the opponent.
+    pub(crate) fn passed(&self) -> Self {
+        let mut position = *self;
+        position.player = self.player.other();
+        position.hash ^= zobrist::side();
+        position
+    }
+
     /// Determine the score after the game is finished.
     ///
     /// Scores are awarded to the current player. Positive scores are a win, negative scores are a loss.
     pub fn score(&self) -> Option<Score> {
         if self.finished() {
-            let mut first: Stones = self.bowls[0..self.size].iter().cloned().sum();
-            first += self.capture[0];
-            let mut second: Stones = self.bowls[self.size..2 * self.size].iter().cloned().sum();
-            second += self.capture[1];
-
-            let score = first as Score - second as Score;
-            Some(score)
+            Some(self.variant.ruleset().score(self))
         } else {
             None
         }
     }
 
+    /// Determine the score for a player after the game is finished.
+    ///
+    /// Positive scores are a win for that player, negative scores are a loss.
+    pub fn score_for(&self, player: Player) -> Option<Score> {
+        self.score()
+            .map(|score| if player == self.player { score } else { -score })
+    }
+
+    /// Determine the outcome after the game is finished.
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.score_for(Player::Red).map(|margin| Outcome { margin })
+    }
+
+    /// The number of stones in a bowl of a player.
+    ///
+    /// Bowls are indexed in sowing order, starting at 0. Panics if the player does not have the bowl.
+    pub fn stones_in(&self, player: Player, bowl: Bowl) -> Stones {
+        assert!(bowl < self.size, "bowl {} out of range", bowl);
+        match player {
+            Player::Red => self.bowls[bowl],
+            Player::Blue => self.bowls[self.size + bowl],
+        }
+    }
+
+    /// The number of stones in the store of a player.
+    pub fn store(&self, player: Player) -> Stones {
+        self.capture[store_index(player)]
+    }
+
+    /// The number of stones in the game, in the bowls and in the stores.
+    pub fn total(&self) -> Stones {
+        self.bowls[0..2 * self.size].iter().sum::<Stones>() + self.capture[0] + self.capture[1]
+    }
+
     /// Difference between the actual captured stones
     pub fn delta(&self) -> Score {
-        self.capture[0] as Score - self.capture[1] as Score
+        self.stones(Pit::Store) as Score - self.stones(Pit::OpponentStore) as Score
     }
 
     /// Return which players turn it is
@@ -267,21 +639,43 @@ impl Position {
     }
 }
 
-impl Display for Position {
+fn store_index(player: Player) -> usize {
+    match player {
+        Player::Red => 0,
+        Player::Blue => 1,
+    }
+}
+
+impl Hash for Position {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+impl Debug for Position {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        let current_side = &self.bowls[0..self.size];
-        let opposite_side: &Vec<Stones> = &self.bowls[self.size..].iter().cloned().rev().collect();
+        f.debug_struct("Position")
+            .field("player", &self.player)
+            .field("size", &self.size)
+            .field("capture", &self.capture)
+            .field("bowls", &&self.bowls[0..2 * self.size])
+            .field("variant", &self.variant)
+            .finish()
+    }
+}
 
-        write!(f, "{:<3}", self.capture[1])?;
-        for stones in opposite_side {
-            write!(f, "| {:<3} ", stones)?
+impl Display for Position {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{:<3}", self.stones(Pit::OpponentStore))?;
+        for bowl in (self.size..2 * self.size).rev() {
+            write!(f, "| {:<3} ", self.stones(Pit::Bowl(bowl)))?
         }
         writeln!(f, "|")?;
         write!(f, "{:<3}", "")?;
-        for stones in current_side {
-            write!(f, "| {:<3} ", stones)?
+        for bowl in 0..self.size {
+            write!(f, "| {:<3} ", self.stones(Pit::Bowl(bowl)))?
         }
-        writeln!(f, "| {:<3}", self.capture[0])
+        writeln!(f, "| {:<3}", self.stones(Pit::Store))
     }
 }
 
@@ -293,52 +687,211 @@ mod tests {
     where
         P: Into<Position>,
     {
+        let current = position.into();
         PlayedGameBuilder {
-            current: position.into(),
+            start: current,
+            current,
         }
     }
 
     struct PlayedGameBuilder {
+        start: Position,
         current: Position,
     }
 
     impl PlayedGameBuilder {
+        fn starting_from<P>(self, start: P) -> Self
+        where
+            P: Into<Position>,
+        {
+            PlayedGameBuilder {
+                start: start.into(),
+                ..self
+            }
+        }
+
         fn with_history(self, history: Vec<(Player, Bowl)>) -> Game {
             Game {
+                start: self.start,
                 current: self.current,
                 history,
+                undone: vec![],
             }
         }
     }
 
     #[test]
     fn fresh_game_is_not_finished() {
-        let game = GameBuilder::new().bowls(6).stones(4).build();
+        let game = GameBuilder::new()
+            .bowls(6)
+            .stones(4)
+            .build()
+            .expect("a playable game");
 
         assert!(!game.finished());
     }
 
+    #[test]
+    fn big_games_can_be_played() -> Result<(), FoulPlay> {
+        let mut game = GameBuilder::new()
+            .bowls(12)
+            .stones(12)
+            .build()
+            .expect("a playable game");
+
+        while !game.finished() {
+            game.play(game.options()[0])?;
+        }
+
+        assert!(game.score().is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn games_with_too_many_stones_can_not_be_built() {
+        let game = GameBuilder::new().bowls(128).stones(128).build();
+
+        assert_eq!(game, Err(BadSetup::TooManyStones));
+    }
+
+    #[test]
+    fn boards_can_have_as_many_bowls_as_before() -> Result<(), FoulPlay> {
+        let mut game = GameBuilder::new()
+            .bowls(255)
+            .stones(1)
+            .build()
+            .expect("a playable game");
+
+        game.play(254)?;
+
+        assert_eq!(game.turn(), Player::Red);
+        Ok(())
+    }
+
+    #[test]
+    fn games_with_too_many_bowls_can_not_be_built() {
+        let game = GameBuilder::new().bowls(MAX_BOWLS + 1).build();
+
+        assert_eq!(game, Err(BadSetup::TooManyBowls));
+    }
+
     #[test]
     fn game_knows_options_to_play() {
-        let game = GameBuilder::new().bowls(3).stones(2).build();
+        let game = GameBuilder::new()
+            .bowls(3)
+            .stones(2)
+            .build()
+            .expect("a playable game");
 
         let options = game.options();
 
         assert_eq!(options, vec!(0, 1, 2));
     }
 
+    #[test]
+    fn positions_know_whether_there_are_options() {
+        assert!(Position::new(3, 2).has_options());
+        assert!(!Position::from([0, 0, 2, 2]).has_options());
+    }
+
+    #[test]
+    fn positions_count_every_stone() {
+        assert_eq!(Position::new(3, 2).total(), 12);
+        assert_eq!(Position::from((5, 1, [0, 0, 2, 2])).total(), 10);
+    }
+
     #[test]
     fn game_records_history_of_what_is_played() -> Result<(), FoulPlay> {
-        let mut actual = GameBuilder::new().bowls(3).stones(2).build();
+        let mut actual = GameBuilder::new()
+            .bowls(3)
+            .stones(2)
+            .build()
+            .expect("a playable game");
 
         actual.play(0)?;
 
         let position = (Player::Blue, [2, 2, 2, 0, 3, 3]);
-        let expected = from_position(position).with_history(vec![(Player::Red, 0)]);
+        let expected = from_position(position)
+            .starting_from([2, 2, 2, 2, 2, 2])
+            .with_history(vec![(Player::Red, 0)]);
         assert_eq!(actual, expected);
         Ok(())
     }
 
+    #[test]
+    fn undo_takes_back_the_last_play() -> Result<(), FoulPlay> {
+        let mut game = GameBuilder::new()
+            .bowls(3)
+            .stones(2)
+            .build()
+            .expect("a playable game");
+        game.play(0)?;
+        game.play(1)?;
+
+        let play = game.undo();
+
+        assert_eq!(play, Some((Player::Blue, 1)));
+        assert_eq!(
+            game.current,
+            Position::from((Player::Blue, [2, 2, 2, 0, 3, 3]))
+        );
+        assert_eq!(game.history(), &[(Player::Red, 0)]);
+        Ok(())
+    }
+
+    #[test]
+    fn redo_plays_what_was_undone() -> Result<(), FoulPlay> {
+        let mut game = GameBuilder::new()
+            .bowls(3)
+            .stones(2)
+            .build()
+            .expect("a playable game");
+        game.play(0)?;
+        let expected = game.current;
+        game.undo();
+
+        let play = game.redo();
+
+        assert_eq!(play, Some((Player::Red, 0)));
+        assert_eq!(game.current, expected);
+        assert_eq!(game.redo(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn playing_forgets_what_was_undone() -> Result<(), FoulPlay> {
+        let mut game = GameBuilder::new()
+            .bowls(3)
+            .stones(2)
+            .build()
+            .expect("a playable game");
+        game.play(0)?;
+        game.undo();
+
+        game.play(1)?;
+
+        assert_eq!(game.redo(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn positions_are_rebuilt_from_the_start() -> Result<(), FoulPlay> {
+        let mut game = GameBuilder::new()
+            .bowls(3)
+            .stones(2)
+            .build()
+            .expect("a playable game");
+        game.play(0)?;
+        game.play(1)?;
+
+        let positions: Vec<Position> = game.positions().collect();
+
+        assert_eq!(positions.len(), 3);
+        assert_eq!(positions[0], *game.start());
+        assert_eq!(positions[2], game.current);
+        Ok(())
+    }
+
     #[test]
     fn play_that_goes_over_store_should_capture_stone() {
         let start = Position::from([2, 2, 2, 2]);
@@ -346,7 +899,7 @@ mod tests {
         let actual = start.play(1);
 
         let expected = Position::from((Player::Blue, 0, 1, [3, 2, 2, 0]));
-        assert_eq!(actual, Some(expected))
+        assert_eq!(actual, Ok(expected))
     }
 
     #[test]
@@ -356,7 +909,7 @@ mod tests {
         let actual = start.play(0);
 
         let expected = Position::from((Player::Blue, 0, 1, [7, 7, 1, 8]));
-        assert_eq!(actual, Some(expected))
+        assert_eq!(actual, Ok(expected))
     }
 
     #[test]
@@ -366,7 +919,7 @@ mod tests {
         let actual = start.play(1);
 
         let expected = Position::from((1, 0, [2, 0, 3, 2, 2, 2]));
-        assert_eq!(actual, Some(expected))
+        assert_eq!(actual, Ok(expected))
     }
 
     #[test]
@@ -376,7 +929,178 @@ mod tests {
         let actual = start.play(0);
 
         let expected = Position::from((Player::Blue, 0, 2, [2, 0, 2, 2, 0, 3, 1, 2]));
-        assert_eq!(actual, Some(expected))
+        assert_eq!(actual, Ok(expected))
+    }
+
+    #[test]
+    fn play_into_empty_bowl_can_capture_both_stones() {
+        let kalah = Kalah::new().capturing(Capture::Both);
+        let start = Position::from([2, 2, 0, 2, 2, 2, 2, 2]).with_rules(kalah);
+
+        let actual = start.play(0);
+
+        let expected =
+            Position::from((Player::Blue, 0, 3, [2, 0, 2, 2, 0, 3, 0, 2])).with_rules(kalah);
+        assert_eq!(actual, Ok(expected))
+    }
+
+    #[test]
+    fn play_into_empty_bowl_opposite_empty_bowl_captures_capturing_stone() {
+        let kalah = Kalah::new().capturing(Capture::Both);
+        let start = Position::from([2, 2, 0, 2, 2, 0, 2, 2]).with_rules(kalah);
+
+        let actual = start.play(0);
+
+        let expected =
+            Position::from((Player::Blue, 0, 1, [2, 0, 2, 2, 0, 3, 0, 2])).with_rules(kalah);
+        assert_eq!(actual, Ok(expected))
+    }
+
+    #[test]
+    fn play_into_empty_bowl_opposite_empty_bowl_can_capture_nothing() {
+        let kalah = Kalah::new()
+            .capturing(Capture::Both)
+            .capturing_from_empty(false);
+        let start = Position::from([2, 2, 0, 2, 2, 0, 2, 2]).with_rules(kalah);
+
+        let actual = start.play(0);
+
+        let expected =
+            Position::from((Player::Blue, 0, 0, [2, 0, 2, 2, 0, 3, 1, 2])).with_rules(kalah);
+        assert_eq!(actual, Ok(expected))
+    }
+
+    #[test]
+    fn stones_left_can_be_swept_to_the_opponent() {
+        let kalah = Kalah::new().sweeping_to(Sweep::Opponent);
+        let start = Position::from([0, 0, 2, 2]).with_rules(kalah);
+
+        assert!(start.finished());
+        assert_eq!(start.score(), Some(4));
+    }
+
+    #[test]
+    fn game_builder_configures_kalah() {
+        let game = GameBuilder::new()
+            .capture(Capture::Both)
+            .capture_from_empty(false)
+            .sweep(Sweep::Opponent)
+            .build()
+            .expect("a playable game");
+
+        let expected = Kalah::new()
+            .capturing(Capture::Both)
+            .capturing_from_empty(false)
+            .sweeping_to(Sweep::Opponent);
+        assert_eq!(game.current.variant(), Variant::Kalah(expected));
+    }
+
+    #[test]
+    fn playing_an_empty_bowl_is_foul_play() {
+        let start = Position::from([0, 2, 2, 2]);
+
+        assert_eq!(start.play(0), Err(FoulPlay::NoStonesInBowl));
+    }
+
+    #[test]
+    fn playing_a_bowl_of_the_opponent_is_foul_play() {
+        let start = Position::from([2, 2, 2, 2]);
+
+        assert_eq!(start.play(2), Err(FoulPlay::OpponentsBowl));
+    }
+
+    #[test]
+    fn playing_a_bowl_that_is_not_on_the_board_is_foul_play() {
+        let start = Position::from([2, 2, 2, 2]);
+
+        assert_eq!(start.play(4), Err(FoulPlay::BowlOutOfRange));
+    }
+
+    #[test]
+    fn playing_a_finished_game_is_foul_play() {
+        let mut game = from_position([0, 0, 2, 2]).with_history(vec![]);
+
+        assert_eq!(game.play(0), Err(FoulPlay::GameAlreadyFinished));
+        assert_eq!(game.current, Position::from([0, 0, 2, 2]));
+    }
+
+    #[test]
+    fn unmake_takes_back_a_play() -> Result<(), FoulPlay> {
+        let start = Position::from([2, 2, 0, 2, 2, 2, 2, 2]);
+        let mut position = start;
+
+        let undo = position.make(0)?;
+        position.unmake(undo);
+
+        assert_eq!(position, start);
+        Ok(())
+    }
+
+    #[test]
+    fn hash_key_is_kept_up_to_date() -> Result<(), FoulPlay> {
+        let mut position = Position::new(4, 4);
+
+        for bowl in [0, 3, 1, 2, 0] {
+            let options = position.options();
+            position = position.play(options[bowl % options.len()])?;
+            assert_eq!(position.hash_key(), position.rehash());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn hash_key_depends_on_the_side_to_move() {
+        let red = Position::from((Player::Red, [1, 2, 3, 4]));
+        let blue = Position::from((Player::Blue, [3, 4, 1, 2]));
+
+        assert_ne!(red.hash_key(), blue.hash_key());
+    }
+
+    #[test]
+    fn hash_key_depends_on_the_variant() {
+        let kalah = Position::new(3, 3);
+        let both = kalah.with_rules(Kalah::new().capturing(Capture::Both));
+        let oware = kalah.with_rules(Oware::new());
+
+        assert_ne!(kalah.hash_key(), both.hash_key());
+        assert_ne!(kalah.hash_key(), oware.hash_key());
+        assert_eq!(oware.hash_key(), oware.rehash());
+        assert_eq!(oware.with_rules(Kalah::new()).hash_key(), kalah.hash_key());
+    }
+
+    #[test]
+    fn equal_positions_have_the_same_hash_key() -> Result<(), FoulPlay> {
+        let played = Position::from([2, 2, 2, 2]).play(1)?;
+        let constructed = Position::from((Player::Blue, 0, 1, [3, 2, 2, 0]));
+
+        assert_eq!(played, constructed);
+        assert_eq!(played.hash_key(), constructed.hash_key());
+        Ok(())
+    }
+
+    #[test]
+    fn unmake_restores_the_hash_key() -> Result<(), FoulPlay> {
+        let mut position = Position::new(3, 3);
+        let expected = position.hash_key();
+
+        let undo = position.make(2)?;
+        position.unmake(undo);
+
+        assert_eq!(position.hash_key(), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn positions_can_be_used_as_keys() -> Result<(), FoulPlay> {
+        let mut seen = std::collections::HashSet::new();
+        let position = Position::new(3, 3);
+
+        seen.insert(position);
+        seen.insert(position.play(0)?);
+
+        assert!(seen.contains(&Position::new(3, 3)));
+        assert_eq!(seen.len(), 2);
+        Ok(())
     }
 
     #[test]
@@ -387,6 +1111,47 @@ mod tests {
         assert_eq!(start.score(), Some(-4));
     }
 
+    #[test]
+    fn board_can_be_viewed_independent_of_the_player_to_move() {
+        let position = Position::from((Player::Blue, 1, 3, [1, 2, 3, 4]));
+
+        assert_eq!(position.stones_in(Player::Red, 0), 3);
+        assert_eq!(position.stones_in(Player::Red, 1), 4);
+        assert_eq!(position.stones_in(Player::Blue, 0), 1);
+        assert_eq!(position.stones_in(Player::Blue, 1), 2);
+        assert_eq!(position.store(Player::Red), 3);
+        assert_eq!(position.store(Player::Blue), 1);
+    }
+
+    #[test]
+    fn scores_can_be_determined_for_either_player() {
+        let position = Position::from((Player::Blue, 0, 1, [0, 0, 0, 1]));
+
+        assert_eq!(position.score_for(Player::Blue), Some(-2));
+        assert_eq!(position.score_for(Player::Red), Some(2));
+    }
+
+    #[test]
+    fn outcome_is_seen_from_red() {
+        let position = Position::from((Player::Blue, 0, 1, [0, 0, 0, 1]));
+
+        let outcome = position.outcome().expect("a finished position");
+
+        assert_eq!(outcome.winner(), Some(Player::Red));
+        assert_eq!(outcome.margin(), 2);
+        assert_eq!(outcome.to_string(), "Red wins by 2");
+    }
+
+    #[test]
+    fn equal_scores_are_a_draw() {
+        let position = Position::from((3, 3, [0, 0, 0, 0]));
+
+        let outcome = position.outcome().expect("a finished position");
+
+        assert!(outcome.is_draw());
+        assert_eq!(outcome.winner(), None);
+    }
+
     #[test]
     fn play_changes_player() {
         let start = Position::from([1, 0, 1, 0]);
@@ -404,12 +1169,7 @@ macro_rules! position_from_array_for_sizes {
         $(
             impl From<[Stones; $n]> for Position {
                 fn from(bowls: [Stones; $n]) -> Self {
-                    Position {
-                        player: Player::Red,
-                        size: $n/2,
-                        capture: [0, 0],
-                        bowls: bowls.to_vec(),
-                    }
+                    Position::relative(Player::Red, [0, 0], &bowls)
                 }
             }
         )*
@@ -421,12 +1181,7 @@ macro_rules! position_with_player_from_array_for_sizes {
         $(
         impl From<(Player, [Stones; $n])> for Position {
             fn from(data: (Player, [Stones; $n])) -> Self {
-                Position {
-                    player: data.0,
-                    size: $n/2,
-                    capture: [0, 0],
-                    bowls: data.1.to_vec(),
-                }
+                Position::relative(data.0, [0, 0], &data.1)
             }
         }
         )*
@@ -438,12 +1193,7 @@ macro_rules! position_with_capture_from_array_for_sizes {
         $(
             impl From<(Stones, Stones, [Stones; $n])> for Position {
                 fn from(data: (Stones, Stones, [Stones; $n])) -> Self {
-                    Position {
-                        player: Player::Red,
-                        size: $n/2,
-                        capture: [data.0, data.1],
-                        bowls: data.2.to_vec(),
-                    }
+                    Position::relative(Player::Red, [data.0, data.1], &data.2)
                 }
             }
         )*
@@ -455,12 +1205,7 @@ macro_rules! position_with_player_with_capture_from_array_for_sizes {
         $(
             impl From<(Player, Stones, Stones, [Stones; $n])> for Position {
                 fn from(data: (Player, Stones, Stones, [Stones; $n])) -> Self {
-                    Position {
-                        player: data.0,
-                        size: $n/2,
-                        capture: [data.1, data.2],
-                        bowls: data.3.to_vec(),
-                    }
+                    Position::relative(data.0, [data.1, data.2], &data.3)
                 }
             }
         )*
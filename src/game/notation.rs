@@ -0,0 +1,206 @@
+//! A compact textual notation for positions.
+//!
+//! Inspired by [Forsyth–Edwards Notation](https://en.wikipedia.org/wiki/Forsyth%E2%80%93Edwards_Notation), a position
+//! is written as four fields separated by a space.
+//!
+//! 1. The bowls. First the bowls of `Red`, then a `/`, then the bowls of `Blue`. Each side is listed in sowing order,
+//!    the number of stones in a bowl separated by a `,`.
+//! 2. The number of stones in the store of `Red`.
+//! 3. The number of stones in the store of `Blue`.
+//! 4. The player to move, `r` for `Red` and `b` for `Blue`.
+//!
+//! The starting position of a game with 6 bowls and 4 stones per bowl is written as
+//!
+//! ```text
+//! 4,4,4,4,4,4/4,4,4,4,4,4 0 0 r
+//! ```
+//!
+//! The notation only describes the board. A position read from notation is played under the default rules, use
+//! `Position::with_rules` to play it under different rules.
+//!
+//! ```rust
+//! # use mancala::game::Position;
+//! let position: Position = "4,4,4/4,4,4 0 0 r".parse().expect("a valid position");
+//!
+//! assert_eq!(position, Position::new(3, 4));
+//! assert_eq!(position.notation().to_string(), "4,4,4/4,4,4 0 0 r");
+//! ```
+
+use super::{Player, Position, Score, Stones, MAX_BOWLS};
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// Discriminates between all the ways notation can be malformed.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum MalformedNotation {
+    /// The notation does not have exactly four fields.
+    WrongNumberOfFields,
+    /// The bowls are not separated in two sides by a `/`.
+    MissingSides,
+    /// The sides have a different number of bowls.
+    UnevenSides,
+    /// The sides have no bowls.
+    EmptySides,
+    /// A side has more than `MAX_BOWLS` bowls.
+    TooManyBowls,
+    /// A number of stones could not be read.
+    NotANumber,
+    /// The total number of stones does not fit in a `Score`.
+    TooManyStones,
+    /// The player to move is neither `r` nor `b`.
+    UnknownPlayer,
+}
+
+/// Formats a position in notation.
+pub struct Notation<'a> {
+    position: &'a Position,
+}
+
+impl Position {
+    /// The notation for this position.
+    pub fn notation(&self) -> Notation<'_> {
+        Notation { position: self }
+    }
+}
+
+impl<'a> Display for Notation<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let position = self.position;
+        let side = |bowls: &[Stones]| {
+            bowls
+                .iter()
+                .map(|stones| stones.to_string())
+                .collect::<Vec<String>>()
+                .join(",")
+        };
+        write!(
+            f,
+            "{}/{} {} {} {}",
+            side(&position.bowls[0..position.size]),
+            side(&position.bowls[position.size..2 * position.size]),
+            position.capture[0],
+            position.capture[1],
+            match position.player {
+                Player::Red => "r",
+                Player::Blue => "b",
+            }
+        )
+    }
+}
+
+impl FromStr for Position {
+    type Err = MalformedNotation;
+
+    fn from_str(notation: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = notation.split_whitespace().collect();
+        if fields.len() != 4 {
+            return Err(MalformedNotation::WrongNumberOfFields);
+        }
+        let sides: Vec<&str> = fields[0].split('/').collect();
+        if sides.len() != 2 {
+            return Err(MalformedNotation::MissingSides);
+        }
+        let red = side(sides[0])?;
+        let blue = side(sides[1])?;
+        if red.len() != blue.len() {
+            return Err(MalformedNotation::UnevenSides);
+        }
+        if red.is_empty() {
+            return Err(MalformedNotation::EmptySides);
+        }
+        if red.len() > MAX_BOWLS {
+            return Err(MalformedNotation::TooManyBowls);
+        }
+        let capture = [stones(fields[1])?, stones(fields[2])?];
+        let player = match fields[3] {
+            "r" => Player::Red,
+            "b" => Player::Blue,
+            _ => return Err(MalformedNotation::UnknownPlayer),
+        };
+
+        let total = red
+            .iter()
+            .chain(blue.iter())
+            .chain(capture.iter())
+            .try_fold(0 as Score, |total, stones| {
+                Score::try_from(*stones)
+                    .ok()
+                    .and_then(|stones| total.checked_add(stones))
+            });
+        if total.is_none() {
+            return Err(MalformedNotation::TooManyStones);
+        }
+
+        let size = red.len();
+        let mut bowls = [0; 2 * MAX_BOWLS];
+        bowls[0..size].copy_from_slice(&red);
+        bowls[size..2 * size].copy_from_slice(&blue);
+        Ok(Position::absolute(player, size, capture, bowls))
+    }
+}
+
+fn side(side: &str) -> Result<Vec<Stones>, MalformedNotation> {
+    if side.is_empty() {
+        Ok(vec![])
+    } else {
+        side.split(',').map(stones).collect()
+    }
+}
+
+fn stones(stones: &str) -> Result<Stones, MalformedNotation> {
+    stones.parse().map_err(|_| MalformedNotation::NotANumber)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notation_is_read_from_the_perspective_of_red() {
+        let position: Position = "1,8/7,7 1 0 b".parse().expect("a valid position");
+
+        assert_eq!(position, Position::from((Player::Blue, 0, 1, [7, 7, 1, 8])));
+    }
+
+    #[test]
+    fn notation_round_trips() {
+        let notation = "0,3,1,2/2,0,2,2 5 3 b";
+
+        let position: Position = notation.parse().expect("a valid position");
+
+        assert_eq!(position.notation().to_string(), notation);
+    }
+
+    #[test]
+    fn malformed_notation_is_rejected() {
+        assert_eq!(
+            "4,4/4,4 0 0".parse::<Position>(),
+            Err(MalformedNotation::WrongNumberOfFields)
+        );
+        assert_eq!(
+            "4,4,4,4 0 0 r".parse::<Position>(),
+            Err(MalformedNotation::MissingSides)
+        );
+        assert_eq!(
+            "4,4/4 0 0 r".parse::<Position>(),
+            Err(MalformedNotation::UnevenSides)
+        );
+        assert_eq!(
+            "/ 0 0 r".parse::<Position>(),
+            Err(MalformedNotation::EmptySides)
+        );
+        assert_eq!(
+            "4,x/4,4 0 0 r".parse::<Position>(),
+            Err(MalformedNotation::NotANumber)
+        );
+        assert_eq!(
+            "4,4/4,4 0 0 g".parse::<Position>(),
+            Err(MalformedNotation::UnknownPlayer)
+        );
+        assert_eq!(
+            "60000/1 0 0 r".parse::<Position>(),
+            Err(MalformedNotation::TooManyStones)
+        );
+    }
+}
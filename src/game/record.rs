@@ -0,0 +1,443 @@
+//! A record format for whole games.
+//!
+//! Inspired by [Portable Game Notation](https://en.wikipedia.org/wiki/Portable_Game_Notation), a record starts with a
+//! header of tags, one per line, followed by an empty line and the plays of the game.
+//!
+//! ```text
+//! [Variant "kalah"]
+//! [Bowls "2"]
+//! [Stones "2"]
+//! [Capture "opposite"]
+//! [CaptureFromEmpty "true"]
+//! [Sweep "owner"]
+//! [Red "Alice"]
+//! [Blue "Bob"]
+//! [Date "2020.04.01"]
+//! [Result "0"]
+//!
+//! r0 r1 b0 r0 b1 r0
+//! ```
+//!
+//! The `Variant`, `Bowls` and `Stones` tags describe the setup of the game. The `Capture`, `CaptureFromEmpty` and
+//! `Sweep` tags are only present for `Kalah`. The `Result` is the score of the game for `Red`, or `*` when the game is
+//! not finished. Within a value a `"`, a `\` and a newline are escaped as `\"`, `\\` and `\n`.
+//!
+//! Each play is the player that makes it, `r` for `Red` and `b` for `Blue`, followed by the bowl played.
+//!
+//! A game is read from its record by replaying every play. So a record that contains foul play, or a result that
+//! does not match the plays, is rejected.
+//!
+//! ```rust
+//! # use mancala::game::{GameBuilder, Game, Header};
+//! let mut game = GameBuilder::new().bowls(2).stones(2).build().expect("a playable game");
+//! game.play(1).expect("a legal play");
+//!
+//! let record = game.record(&Header::default()).to_string();
+//! let (read, _) = Game::from_record(&record).expect("a valid record");
+//!
+//! assert_eq!(read, game);
+//! ```
+
+use super::{
+    BadSetup, Bowl, Capture, FoulPlay, Game, GameBuilder, Kalah, Oware, Pit, Player, Ruleset,
+    Stones, Sweep, Variant,
+};
+use std::fmt::{self, Display, Formatter};
+
+/// Information about a game that does not follow from the plays.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Header {
+    /// The name of the `Red` player
+    pub red: String,
+    /// The name of the `Blue` player
+    pub blue: String,
+    /// The date the game was played, preferably as `YYYY.MM.DD`
+    pub date: String,
+}
+
+impl Default for Header {
+    /// Every field is unknown, written as `?`.
+    fn default() -> Self {
+        Header {
+            red: "?".to_string(),
+            blue: "?".to_string(),
+            date: "?".to_string(),
+        }
+    }
+}
+
+/// Discriminates between all the ways a record can be rejected.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum BadRecord {
+    /// A line in the header is not a tag of the form `[Name "value"]`.
+    MalformedTag(String),
+    /// A tag that describes the setup of the game is missing.
+    MissingTag(&'static str),
+    /// The value of a tag could not be understood.
+    MalformedValue(&'static str),
+    /// The variant is not known.
+    UnknownVariant(String),
+    /// The game can not be set up.
+    BadSetup(BadSetup),
+    /// The play with this index could not be read.
+    MalformedPlay(usize),
+    /// The play with this index is made by the player that is not on turn.
+    WrongPlayer(usize),
+    /// The play with this index is foul play.
+    IllegalPlay(usize, FoulPlay),
+    /// The result does not match the plays.
+    ResultMismatch,
+}
+
+/// Formats a game as a record.
+pub struct Record<'a> {
+    game: &'a Game,
+    header: &'a Header,
+}
+
+impl Game {
+    /// The record of this game.
+    pub fn record<'a>(&'a self, header: &'a Header) -> Record<'a> {
+        Record { game: self, header }
+    }
+
+    /// Read a game from its record.
+    ///
+    /// Only the variants of this crate are known. Use `from_record_with` for house variants.
+    pub fn from_record(record: &str) -> Result<(Game, Header), BadRecord> {
+        Game::from_record_with(record, &[])
+    }
+
+    /// Read a game from its record, knowing about house variants.
+    pub fn from_record_with(
+        record: &str,
+        houses: &[&'static dyn Ruleset],
+    ) -> Result<(Game, Header), BadRecord> {
+        let mut lines = record
+            .lines()
+            .map(str::trim)
+            .skip_while(|line| line.is_empty());
+        let mut tags = vec![];
+        for line in &mut lines {
+            if line.is_empty() {
+                break;
+            }
+            tags.push(tag(line)?);
+        }
+        let value = |name: &str| {
+            tags.iter()
+                .find(|(candidate, _)| *candidate == name)
+                .map(|(_, value)| value.as_str())
+        };
+        let required = |name: &'static str| value(name).ok_or(BadRecord::MissingTag(name));
+
+        let variant = variant(required("Variant")?, &value, houses)?;
+        let bowls = required("Bowls")?
+            .parse::<usize>()
+            .map_err(|_| BadRecord::MalformedValue("Bowls"))?;
+        let stones = required("Stones")?
+            .parse::<Stones>()
+            .map_err(|_| BadRecord::MalformedValue("Stones"))?;
+        let mut game = GameBuilder::new()
+            .bowls(bowls)
+            .stones(stones)
+            .rules(variant)
+            .build()
+            .map_err(BadRecord::BadSetup)?;
+
+        let plays = lines.flat_map(str::split_whitespace);
+        for (index, play) in plays.enumerate() {
+            let (player, bowl) = self::play(play).ok_or(BadRecord::MalformedPlay(index))?;
+            if player != game.turn() {
+                return Err(BadRecord::WrongPlayer(index));
+            }
+            game.play(bowl)
+                .map_err(|foul_play| BadRecord::IllegalPlay(index, foul_play))?;
+        }
+
+        if let Some(result) = value("Result") {
+            if result != self::result(&game) {
+                return Err(BadRecord::ResultMismatch);
+            }
+        }
+
+        let known = |name: &str| value(name).unwrap_or("?").to_string();
+        let header = Header {
+            red: known("Red"),
+            blue: known("Blue"),
+            date: known("Date"),
+        };
+        Ok((game, header))
+    }
+}
+
+impl<'a> Display for Record<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let start = &self.game.start;
+        let variant = start.variant();
+        writeln!(f, "[Variant \"{}\"]", variant.ruleset().name())?;
+        writeln!(f, "[Bowls \"{}\"]", start.size())?;
+        writeln!(f, "[Stones \"{}\"]", start.stones(Pit::Bowl(0)))?;
+        if let Variant::Kalah(kalah) = variant {
+            let capture = match kalah.capture_rule() {
+                Capture::Opposite => "opposite",
+                Capture::Both => "both",
+            };
+            let sweep = match kalah.sweep_rule() {
+                Sweep::Owner => "owner",
+                Sweep::Opponent => "opponent",
+            };
+            writeln!(f, "[Capture \"{}\"]", capture)?;
+            writeln!(f, "[CaptureFromEmpty \"{}\"]", kalah.captures_from_empty())?;
+            writeln!(f, "[Sweep \"{}\"]", sweep)?;
+        }
+        writeln!(f, "[Red \"{}\"]", escape(&self.header.red))?;
+        writeln!(f, "[Blue \"{}\"]", escape(&self.header.blue))?;
+        writeln!(f, "[Date \"{}\"]", escape(&self.header.date))?;
+        writeln!(f, "[Result \"{}\"]", result(self.game))?;
+        writeln!(f)?;
+        for (index, (player, bowl)) in self.game.history.iter().enumerate() {
+            let separator = match index {
+                0 => "",
+                _ if index % 10 == 0 => "\n",
+                _ => " ",
+            };
+            write!(f, "{}{}{}", separator, initial(*player), bowl)?;
+        }
+        writeln!(f)
+    }
+}
+
+fn tag(line: &str) -> Result<(&str, String), BadRecord> {
+    let malformed = || BadRecord::MalformedTag(line.to_string());
+    let inner = line
+        .strip_prefix('[')
+        .and_then(|line| line.strip_suffix(']'))
+        .ok_or_else(malformed)?;
+    let (name, value) = inner.split_once(' ').ok_or_else(malformed)?;
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .and_then(unescape)
+        .ok_or_else(malformed)?;
+    Ok((name, value))
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for character in value.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+fn unescape(value: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut characters = value.chars();
+    while let Some(character) = characters.next() {
+        match character {
+            '\\' => match characters.next()? {
+                '"' => unescaped.push('"'),
+                '\\' => unescaped.push('\\'),
+                'n' => unescaped.push('\n'),
+                _ => return None,
+            },
+            '"' => return None,
+            _ => unescaped.push(character),
+        }
+    }
+    Some(unescaped)
+}
+
+fn variant<'a, F>(
+    name: &str,
+    value: &F,
+    houses: &[&'static dyn Ruleset],
+) -> Result<Variant, BadRecord>
+where
+    F: Fn(&str) -> Option<&'a str>,
+{
+    match name {
+        "kalah" => {
+            let capture = match value("Capture") {
+                None | Some("opposite") => Capture::Opposite,
+                Some("both") => Capture::Both,
+                Some(_) => return Err(BadRecord::MalformedValue("Capture")),
+            };
+            let capture_from_empty = match value("CaptureFromEmpty") {
+                None | Some("true") => true,
+                Some("false") => false,
+                Some(_) => return Err(BadRecord::MalformedValue("CaptureFromEmpty")),
+            };
+            let sweep = match value("Sweep") {
+                None | Some("owner") => Sweep::Owner,
+                Some("opponent") => Sweep::Opponent,
+                Some(_) => return Err(BadRecord::MalformedValue("Sweep")),
+            };
+            Ok(Variant::Kalah(
+                Kalah::new()
+                    .capturing(capture)
+                    .capturing_from_empty(capture_from_empty)
+                    .sweeping_to(sweep),
+            ))
+        }
+        "oware" => Ok(Variant::Oware(Oware::new())),
+        _ => houses
+            .iter()
+            .find(|house| house.name() == name)
+            .map(|house| Variant::House(*house))
+            .ok_or_else(|| BadRecord::UnknownVariant(name.to_string())),
+    }
+}
+
+fn play(play: &str) -> Option<(Player, Bowl)> {
+    let player = match play.chars().next()? {
+        'r' => Player::Red,
+        'b' => Player::Blue,
+        _ => return None,
+    };
+    let bowl = play[1..].parse().ok()?;
+    Some((player, bowl))
+}
+
+fn initial(player: Player) -> &'static str {
+    match player {
+        Player::Red => "r",
+        Player::Blue => "b",
+    }
+}
+
+fn result(game: &Game) -> String {
+    match game.outcome() {
+        Some(outcome) => outcome.margin().to_string(),
+        None => "*".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameBuilder;
+
+    const RECORD: &str = "[Variant \"kalah\"]
+[Bowls \"2\"]
+[Stones \"2\"]
+[Capture \"opposite\"]
+[CaptureFromEmpty \"true\"]
+[Sweep \"owner\"]
+[Red \"Alice\"]
+[Blue \"Bob\"]
+[Date \"2020.04.01\"]
+[Result \"*\"]
+
+r1 b0
+";
+
+    fn header() -> Header {
+        Header {
+            red: "Alice".to_string(),
+            blue: "Bob".to_string(),
+            date: "2020.04.01".to_string(),
+        }
+    }
+
+    fn game() -> Game {
+        let mut game = GameBuilder::new()
+            .bowls(2)
+            .stones(2)
+            .build()
+            .expect("a playable game");
+        game.play(1).expect("a legal play");
+        game.play(0).expect("a legal play");
+        game
+    }
+
+    #[test]
+    fn games_are_written_as_records() {
+        let actual = game().record(&header()).to_string();
+
+        assert_eq!(actual, RECORD);
+    }
+
+    #[test]
+    fn games_are_read_from_records() {
+        let actual = Game::from_record(RECORD);
+
+        assert_eq!(actual, Ok((game(), header())));
+    }
+
+    #[test]
+    fn finished_games_round_trip() {
+        let mut game = GameBuilder::new()
+            .bowls(3)
+            .stones(3)
+            .rules(Oware::new())
+            .build()
+            .expect("a playable game");
+        while !game.finished() {
+            game.play(game.options()[0]).expect("a legal play");
+        }
+
+        let record = game.record(&Header::default()).to_string();
+
+        assert_eq!(Game::from_record(&record), Ok((game, Header::default())));
+    }
+
+    #[test]
+    fn header_values_are_escaped() {
+        let header = Header {
+            red: "Alice \"the \\ sower\"\nof Kalah".to_string(),
+            ..header()
+        };
+
+        let record = game().record(&header).to_string();
+
+        assert!(record.contains("[Red \"Alice \\\"the \\\\ sower\\\"\\nof Kalah\"]"));
+        assert_eq!(Game::from_record(&record), Ok((game(), header)));
+    }
+
+    #[test]
+    fn foul_play_is_rejected() {
+        let record = RECORD.replace("r1 b0", "r1 b3");
+
+        let actual = Game::from_record(&record);
+
+        assert_eq!(
+            actual,
+            Err(BadRecord::IllegalPlay(1, FoulPlay::OpponentsBowl))
+        );
+    }
+
+    #[test]
+    fn plays_out_of_turn_are_rejected() {
+        let record = RECORD.replace("r1 b0", "r1 r0");
+
+        let actual = Game::from_record(&record);
+
+        assert_eq!(actual, Err(BadRecord::WrongPlayer(1)));
+    }
+
+    #[test]
+    fn wrong_results_are_rejected() {
+        let record = RECORD.replace("[Result \"*\"]", "[Result \"2\"]");
+
+        let actual = Game::from_record(&record);
+
+        assert_eq!(actual, Err(BadRecord::ResultMismatch));
+    }
+
+    #[test]
+    fn unknown_variants_are_rejected() {
+        let record = RECORD.replace("kalah", "bao");
+
+        let actual = Game::from_record(&record);
+
+        assert_eq!(actual, Err(BadRecord::UnknownVariant("bao".to_string())));
+    }
+}
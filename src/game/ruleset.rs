@@ -0,0 +1,565 @@
+//! Rules that govern how a play is resolved.
+//!
+//! Mancala is a family of games that share a board, but differ in how stones are sown, when a
+//! player is allowed another turn, what gets captured and how the game ends. A `Ruleset` captures
+//! these differences, so that a `Position` can be played under any of them.
+//!
+//! All the information a ruleset receives is from the perspective of the player making the play.
+//!
+//! ```rust
+//! # use mancala::game::{GameBuilder, Kalah};
+//! let game =
+//!   GameBuilder::new()
+//!     .rules(Kalah::new())
+//!     .build()
+//!     .expect("a playable game");
+//! ```
+
+use super::{Bowl, Position, Score, Stones, MAX_BOWLS};
+use std::fmt::{self, Debug, Formatter};
+
+/// A place on the board that can hold stones, seen from the player making the play.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Pit {
+    /// A bowl. Indices `0..size` are the bowls of the player making the play, indices `size..2*size` are the bowls of the opponent, in sowing order.
+    Bowl(Bowl),
+    /// The store of the player making the play.
+    Store,
+    /// The store of the opponent.
+    OpponentStore,
+}
+
+/// The rules of a mancala variant.
+pub trait Ruleset: Debug + Sync {
+    /// The name of this ruleset.
+    fn name(&self) -> &'static str;
+
+    /// Determine if the bowl can be played.
+    ///
+    /// By default every own bowl that contains stones can be played.
+    fn playable(&self, position: &Position, bowl: Bowl) -> bool {
+        position.stones(Pit::Bowl(bowl)) > 0
+    }
+
+    /// The pit that receives the next stone when sowing from `origin`, given the pit that received the previous stone.
+    fn next(&self, size: usize, origin: Bowl, pit: Pit) -> Pit;
+
+    /// The bowls that are captured when the last stone is sown in `last`.
+    fn capture(&self, position: &Position, last: Pit) -> Captures;
+
+    /// Determine if the player is allowed an other turn when the last stone is sown in `last`.
+    fn extra_turn(&self, position: &Position, last: Pit) -> bool;
+
+    /// Determine if a position is finished.
+    ///
+    /// By default a position is finished when the current player can't make any plays.
+    fn finished(&self, position: &Position) -> bool {
+        !position.has_options()
+    }
+
+    /// Determine the score of a finished position, awarded to the current player.
+    fn score(&self, position: &Position) -> Score;
+}
+
+/// The bowls captured by a play.
+///
+/// The bowls are kept in a fixed size array, so that resolving a play does not allocate.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Captures {
+    bowls: [u16; 2 * MAX_BOWLS],
+    len: usize,
+}
+
+impl Captures {
+    /// No bowls are captured
+    pub fn none() -> Self {
+        Captures {
+            bowls: [0; 2 * MAX_BOWLS],
+            len: 0,
+        }
+    }
+
+    /// Capture a bowl as well.
+    ///
+    /// Panics when more than `2 * MAX_BOWLS` bowls are captured.
+    pub fn push(&mut self, bowl: Bowl) {
+        self.bowls[self.len] = bowl as u16;
+        self.len += 1;
+    }
+
+    /// The captured bowls, in the order they were captured.
+    pub fn bowls(&self) -> impl Iterator<Item = Bowl> + '_ {
+        self.bowls[0..self.len].iter().map(|bowl| *bowl as Bowl)
+    }
+}
+
+impl Default for Captures {
+    fn default() -> Self {
+        Captures::none()
+    }
+}
+
+impl From<&[Bowl]> for Captures {
+    fn from(bowls: &[Bowl]) -> Self {
+        let mut captures = Captures::none();
+        bowls.iter().for_each(|bowl| captures.push(*bowl));
+        captures
+    }
+}
+
+/// What is captured when the last stone lands in an empty bowl of the player.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Capture {
+    /// Only the stones in the opposite bowl are captured, the capturing stone stays in its bowl.
+    Opposite,
+    /// Both the capturing stone and the stones in the opposite bowl are captured.
+    Both,
+}
+
+/// Where the stones left on a side go when the game ends.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Sweep {
+    /// Stones left on a side belong to the owner of that side.
+    Owner,
+    /// Stones left on a side belong to the opponent of the owner of that side.
+    Opponent,
+}
+
+/// The Kalah-like variant this crate started out with.
+///
+/// * Stones are sown through your own store, but skip the store of the opponent.
+/// * When the last stone lands in your store, you are allowed an other turn.
+/// * When the last stone lands in one of your empty bowls, the stones in the opposite bowl are captured.
+/// * The game ends when the current player can't play. Stones left on a side belong to the owner of that side.
+///
+/// Tournaments differ in how captures and the end of the game are handled. The rules above are the defaults,
+/// and can be changed.
+///
+/// ```rust
+/// # use mancala::game::{Capture, Kalah, Sweep};
+/// let kalah = Kalah::new()
+///     .capturing(Capture::Both)
+///     .capturing_from_empty(false)
+///     .sweeping_to(Sweep::Owner);
+/// ```
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Kalah {
+    capture: Capture,
+    capture_from_empty: bool,
+    sweep: Sweep,
+}
+
+impl Kalah {
+    /// Create a Kalah ruleset
+    pub fn new() -> Self {
+        Self {
+            capture: Capture::Opposite,
+            capture_from_empty: true,
+            sweep: Sweep::Owner,
+        }
+    }
+
+    /// Determine what is captured
+    pub fn capturing(self, capture: Capture) -> Self {
+        Self { capture, ..self }
+    }
+
+    /// Determine if a capture happens when the opposite bowl is empty
+    pub fn capturing_from_empty(self, capture_from_empty: bool) -> Self {
+        Self {
+            capture_from_empty,
+            ..self
+        }
+    }
+
+    /// Determine where the stones left at the end of the game go
+    pub fn sweeping_to(self, sweep: Sweep) -> Self {
+        Self { sweep, ..self }
+    }
+
+    /// What is captured
+    pub fn capture_rule(&self) -> Capture {
+        self.capture
+    }
+
+    /// Whether a capture happens when the opposite bowl is empty
+    pub fn captures_from_empty(&self) -> bool {
+        self.capture_from_empty
+    }
+
+    /// Where the stones left at the end of the game go
+    pub fn sweep_rule(&self) -> Sweep {
+        self.sweep
+    }
+}
+
+impl Default for Kalah {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Ruleset for Kalah {
+    fn name(&self) -> &'static str {
+        "kalah"
+    }
+
+    fn next(&self, size: usize, _origin: Bowl, pit: Pit) -> Pit {
+        match pit {
+            Pit::Bowl(bowl) if bowl + 1 == size => Pit::Store,
+            Pit::Bowl(bowl) => Pit::Bowl((bowl + 1) % (2 * size)),
+            Pit::Store => Pit::Bowl(size),
+            Pit::OpponentStore => Pit::Bowl(0),
+        }
+    }
+
+    fn capture(&self, position: &Position, last: Pit) -> Captures {
+        let size = position.size();
+        match last {
+            Pit::Bowl(bowl) if bowl < size && position.stones(last) == 1 => {
+                let opposite = 2 * size - 1 - bowl;
+                if !self.capture_from_empty && position.stones(Pit::Bowl(opposite)) == 0 {
+                    Captures::none()
+                } else {
+                    match self.capture {
+                        Capture::Opposite => Captures::from(&[opposite][..]),
+                        Capture::Both => Captures::from(&[bowl, opposite][..]),
+                    }
+                }
+            }
+            _ => Captures::none(),
+        }
+    }
+
+    fn extra_turn(&self, _position: &Position, last: Pit) -> bool {
+        last == Pit::Store
+    }
+
+    fn score(&self, position: &Position) -> Score {
+        let size = position.size();
+        let own: Stones = (0..size).map(|bowl| position.stones(Pit::Bowl(bowl))).sum();
+        let opponent: Stones = (size..2 * size)
+            .map(|bowl| position.stones(Pit::Bowl(bowl)))
+            .sum();
+        let (first, second) = match self.sweep {
+            Sweep::Owner => (own, opponent),
+            Sweep::Opponent => (opponent, own),
+        };
+        (first + position.stones(Pit::Store)) as Score
+            - (second + position.stones(Pit::OpponentStore)) as Score
+    }
+}
+
+/// Oware, following the abapa rules.
+///
+/// * There are no stores in the sowing path. When twelve or more stones are sown, the bowl they came from is skipped.
+/// * There are no extra turns.
+/// * When the last stone lands in a bowl of the opponent, bringing it to two or three stones, those stones are captured.
+///   So are the stones of the preceding bowls of the opponent, for as long as they contain two or three stones.
+/// * A play that would capture all the stones of the opponent, a grand slam, is allowed, but captures nothing.
+/// * When the opponent has no stones, a play must give them stones. If no play does, the game ends.
+/// * The game ends when a player has captured more than half of the stones, or when the current player can't play.
+///   Stones left on a side belong to the owner of that side.
+///
+/// Endless cycles are not detected. Searching strategies should be limited in depth.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Oware {}
+
+impl Oware {
+    /// Create an Oware ruleset
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Default for Oware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Ruleset for Oware {
+    fn name(&self) -> &'static str {
+        "oware"
+    }
+
+    fn playable(&self, position: &Position, bowl: Bowl) -> bool {
+        let size = position.size();
+        let stones = position.stones(Pit::Bowl(bowl)) as usize;
+        let opponent_starves = (size..2 * size).all(|bowl| position.stones(Pit::Bowl(bowl)) == 0);
+        stones > 0 && (!opponent_starves || bowl + stones >= size)
+    }
+
+    fn next(&self, size: usize, origin: Bowl, pit: Pit) -> Pit {
+        let bowl = match pit {
+            Pit::Bowl(bowl) => bowl,
+            _ => origin,
+        };
+        let next = (bowl + 1) % (2 * size);
+        if next == origin {
+            Pit::Bowl((next + 1) % (2 * size))
+        } else {
+            Pit::Bowl(next)
+        }
+    }
+
+    fn capture(&self, position: &Position, last: Pit) -> Captures {
+        let size = position.size();
+        let mut captured = Captures::none();
+        if let Pit::Bowl(last) = last {
+            let mut bowl = last;
+            while bowl >= size && (2..=3).contains(&position.stones(Pit::Bowl(bowl))) {
+                captured.push(bowl);
+                bowl -= 1;
+            }
+        }
+        let capture: Stones = captured
+            .bowls()
+            .map(|bowl| position.stones(Pit::Bowl(bowl)))
+            .sum();
+        let opponent: Stones = (size..2 * size)
+            .map(|bowl| position.stones(Pit::Bowl(bowl)))
+            .sum();
+        if capture == opponent {
+            Captures::none()
+        } else {
+            captured
+        }
+    }
+
+    fn extra_turn(&self, _position: &Position, _last: Pit) -> bool {
+        false
+    }
+
+    fn finished(&self, position: &Position) -> bool {
+        let size = position.size();
+        let total: Stones = (0..2 * size)
+            .map(|bowl| position.stones(Pit::Bowl(bowl)))
+            .sum::<Stones>()
+            + position.stones(Pit::Store)
+            + position.stones(Pit::OpponentStore);
+        position.stones(Pit::Store) > total / 2
+            || position.stones(Pit::OpponentStore) > total / 2
+            || !position.has_options()
+    }
+
+    fn score(&self, position: &Position) -> Score {
+        Kalah::new().score(position)
+    }
+}
+
+/// The variant a position is played under.
+#[derive(Copy, Clone)]
+pub enum Variant {
+    /// The Kalah-like rules
+    Kalah(Kalah),
+    /// The Oware rules
+    Oware(Oware),
+    /// A ruleset defined outside of this crate
+    House(&'static dyn Ruleset),
+}
+
+impl Variant {
+    /// The ruleset of this variant
+    pub fn ruleset(&self) -> &dyn Ruleset {
+        match self {
+            Variant::Kalah(kalah) => kalah,
+            Variant::Oware(oware) => oware,
+            Variant::House(ruleset) => *ruleset,
+        }
+    }
+}
+
+impl Default for Variant {
+    fn default() -> Self {
+        Variant::Kalah(Kalah::new())
+    }
+}
+
+impl Debug for Variant {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Variant::Kalah(kalah) => kalah.fmt(f),
+            Variant::Oware(oware) => oware.fmt(f),
+            Variant::House(ruleset) => ruleset.fmt(f),
+        }
+    }
+}
+
+impl PartialEq for Variant {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Variant::Kalah(left), Variant::Kalah(right)) => left == right,
+            (Variant::Oware(left), Variant::Oware(right)) => left == right,
+            (Variant::House(left), Variant::House(right)) => left.name() == right.name(),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Variant {}
+
+impl From<Kalah> for Variant {
+    fn from(kalah: Kalah) -> Self {
+        Variant::Kalah(kalah)
+    }
+}
+
+impl From<Oware> for Variant {
+    fn from(oware: Oware) -> Self {
+        Variant::Oware(oware)
+    }
+}
+
+impl<R> From<&'static R> for Variant
+where
+    R: Ruleset,
+{
+    fn from(ruleset: &'static R) -> Self {
+        Variant::House(ruleset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{FoulPlay, GameBuilder, Player};
+
+    fn oware<P>(position: P) -> Position
+    where
+        P: Into<Position>,
+    {
+        position.into().with_rules(Oware::new())
+    }
+
+    #[derive(Debug)]
+    struct NoExtraTurns {}
+
+    impl Ruleset for NoExtraTurns {
+        fn name(&self) -> &'static str {
+            "no extra turns"
+        }
+
+        fn next(&self, size: usize, origin: Bowl, pit: Pit) -> Pit {
+            Kalah::new().next(size, origin, pit)
+        }
+
+        fn capture(&self, position: &Position, last: Pit) -> Captures {
+            Kalah::new().capture(position, last)
+        }
+
+        fn extra_turn(&self, _position: &Position, _last: Pit) -> bool {
+            false
+        }
+
+        fn score(&self, position: &Position) -> Score {
+            Kalah::new().score(position)
+        }
+    }
+
+    static NO_EXTRA_TURNS: NoExtraTurns = NoExtraTurns {};
+
+    #[test]
+    fn kalah_is_the_default_variant() {
+        let game = GameBuilder::new().build().expect("a playable game");
+
+        assert_eq!(game.current.variant(), Variant::Kalah(Kalah::new()));
+    }
+
+    #[test]
+    fn house_rules_are_followed() {
+        let mut game = GameBuilder::new()
+            .bowls(3)
+            .stones(3)
+            .rules(&NO_EXTRA_TURNS)
+            .build()
+            .expect("a playable game");
+
+        game.play(0).expect("bowl to be playable");
+
+        assert_eq!(game.turn(), Player::Blue);
+    }
+
+    #[test]
+    fn sowing_passes_own_store_but_skips_opponents_store() {
+        let kalah = Kalah::new();
+
+        assert_eq!(kalah.next(2, 0, Pit::Bowl(1)), Pit::Store);
+        assert_eq!(kalah.next(2, 0, Pit::Store), Pit::Bowl(2));
+        assert_eq!(kalah.next(2, 0, Pit::Bowl(3)), Pit::Bowl(0));
+    }
+
+    #[test]
+    fn oware_skips_the_origin_bowl() {
+        let start = oware([12, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1]);
+
+        let actual = start.play(0);
+
+        let expected = oware((Player::Blue, [2, 2, 2, 2, 2, 2, 0, 2, 1, 1, 1, 1]));
+        assert_eq!(actual, Ok(expected));
+    }
+
+    #[test]
+    fn oware_captures_twos_and_threes_backwards() {
+        let start = oware([0, 0, 3, 0, 1, 1, 1, 0]);
+
+        let actual = start.play(2);
+
+        let expected = oware((Player::Blue, 0, 4, [0, 0, 1, 0, 0, 0, 0, 1]));
+        assert_eq!(actual, Ok(expected));
+    }
+
+    #[test]
+    fn oware_capture_stops_at_other_counts() {
+        let start = oware([0, 0, 0, 3, 4, 1, 1, 0]);
+
+        let actual = start.play(3);
+
+        let expected = oware((Player::Blue, 0, 4, [5, 0, 0, 0, 0, 0, 0, 0]));
+        assert_eq!(actual, Ok(expected));
+    }
+
+    #[test]
+    fn oware_grand_slam_captures_nothing() {
+        let start = oware([0, 2, 1, 1]);
+
+        let actual = start.play(1);
+
+        let expected = oware((Player::Blue, [2, 2, 0, 0]));
+        assert_eq!(actual, Ok(expected));
+    }
+
+    #[test]
+    fn captures_are_listed_in_order() {
+        let mut captures = Captures::none();
+        captures.push(5);
+        captures.push(4);
+
+        assert_eq!(captures.bowls().collect::<Vec<_>>(), vec![5, 4]);
+        assert_eq!(Captures::from(&[5, 4][..]), captures);
+    }
+
+    #[test]
+    fn oware_requires_feeding_a_starving_opponent() {
+        let start = oware([3, 1, 0, 0, 0, 0]);
+
+        assert_eq!(start.options(), vec![0]);
+        assert_eq!(start.play(1), Err(FoulPlay::ForbiddenByRules));
+    }
+
+    #[test]
+    fn oware_ends_when_the_opponent_can_not_be_fed() {
+        let start = oware([1, 0, 0, 0]);
+
+        assert!(start.finished());
+        assert_eq!(start.score(), Some(1));
+    }
+
+    #[test]
+    fn oware_ends_when_more_than_half_is_captured() {
+        let start = oware((7, 2, [1, 1, 1, 1]));
+
+        assert!(start.finished());
+        assert_eq!(start.score(), Some(5));
+    }
+}
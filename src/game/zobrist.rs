@@ -0,0 +1,59 @@
+//! [Zobrist hashing](https://en.wikipedia.org/wiki/Zobrist_hashing) of positions.
+//!
+//! Every combination of a pit and a number of stones is assigned a random number. The hash of a position is the
+//! exclusive or of the random numbers of all its pits, of a random number for the side to move when that is `Blue`,
+//! and of a random number for the variant. Changing the number of stones in a pit only takes two exclusive ors to
+//! update the hash.
+//!
+//! Instead of a table of random numbers, which would be huge for large numbers of stones, the random numbers are
+//! derived from the pit and the number of stones with [SplitMix64](https://prng.di.unimi.it/splitmix64.c).
+
+use super::{Capture, Stones, Sweep, Variant, MAX_BOWLS};
+
+/// The random number for a pit holding a number of stones.
+///
+/// Pits `0..2 * MAX_BOWLS` are bowls, the following two are the stores. An empty pit does not contribute.
+pub(super) fn pit(pit: usize, stones: Stones) -> u64 {
+    if stones == 0 {
+        0
+    } else {
+        splitmix64(((pit as u64) << 16) | stones as u64)
+    }
+}
+
+/// The random number for `Blue` being the side to move.
+pub(super) fn side() -> u64 {
+    splitmix64(((2 * MAX_BOWLS as u64 + 2) << 16) | 1)
+}
+
+/// The random number for the variant a position is played under.
+///
+/// Kalah variants differ by their options, house rules by their name.
+pub(super) fn variant(variant: &Variant) -> u64 {
+    let seed = match variant {
+        Variant::Kalah(kalah) => {
+            let capture = match kalah.capture_rule() {
+                Capture::Opposite => 0,
+                Capture::Both => 1,
+            };
+            let sweep = match kalah.sweep_rule() {
+                Sweep::Owner => 0,
+                Sweep::Opponent => 1,
+            };
+            capture | (kalah.captures_from_empty() as u64) << 1 | sweep << 2
+        }
+        Variant::Oware(_) => 1 << 8,
+        Variant::House(ruleset) => ruleset
+            .name()
+            .bytes()
+            .fold(2 << 8, |seed, byte| splitmix64(seed ^ byte as u64)),
+    };
+    splitmix64(((2 * MAX_BOWLS as u64 + 3) << 16) ^ seed)
+}
+
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,189 @@
+//! Learn to play Mancala.
+//!
+//! [Reinforcement learning](https://en.wikipedia.org/wiki/Reinforcement_learning) agents improve by playing games
+//! against themselves. Once trained, they can play on their own, or lend their judgement to a tree search.
+//!
+//! * `TemporalDifference` learns a value function with [TD(λ)](https://en.wikipedia.org/wiki/Temporal_difference_learning).
+//! * `QLearning` learns the value of every play in every position of a small board.
+//! * `Network` is a multilayer perceptron that learns to value positions and the plays in them.
+//! * `SelfPlay` trains a `Network` by letting `Zero`, a tree search guided by the network, play against itself.
+
+pub mod network;
+pub mod tabular;
+pub mod td;
+pub mod zero;
+
+pub use self::network::Network;
+pub use self::tabular::QLearning;
+pub use self::td::TemporalDifference;
+pub use self::zero::{SelfPlay, Zero};
+
+use crate::{
+    game::{Player, MAX_BOWLS},
+    tuning::{BadWeights, Weights},
+};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Discriminates between all the ways parameters can be rejected.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum BadParameters {
+    /// The parameters could not be read from disk.
+    Unreadable(io::ErrorKind),
+    /// A line of the header is missing or malformed.
+    MalformedHeader,
+    /// The number of bowls is more than `MAX_BOWLS`.
+    TooManyBowls,
+    /// The weights could not be read.
+    BadWeights(BadWeights),
+    /// The number of weights does not match the header.
+    WrongNumberOfWeights,
+    /// The sample on this line of a replay buffer could not be read.
+    MalformedSample(usize),
+}
+
+/// Read the text of a parameters file.
+fn load<P>(path: P) -> Result<String, BadParameters>
+where
+    P: AsRef<Path>,
+{
+    fs::read_to_string(path).map_err(|error| BadParameters::Unreadable(error.kind()))
+}
+
+/// Read parameters that start with a header line for each of `names`, of the form `name value`, followed by weights.
+///
+/// Returns the values in the header, in the order of `names`. Empty lines and lines starting with `#` are ignored.
+fn read<'a>(parameters: &'a str, names: &[&str]) -> Result<(Vec<&'a str>, Weights), BadParameters> {
+    let mut lines = parameters.lines().enumerate().filter(|(_, line)| {
+        let line = line.trim();
+        !line.is_empty() && !line.starts_with('#')
+    });
+    let mut header = vec![];
+    let mut weights_start = 0;
+    for name in names {
+        let (index, line) = lines.next().ok_or(BadParameters::MalformedHeader)?;
+        let value = line
+            .trim()
+            .strip_prefix(name)
+            .filter(|value| value.starts_with(' '))
+            .ok_or(BadParameters::MalformedHeader)?;
+        header.push(value.trim());
+        weights_start = index + 1;
+    }
+    let rest: Vec<&str> = parameters.lines().skip(weights_start).collect();
+    let weights =
+        rest.join("\n")
+            .parse::<Weights>()
+            .map_err(|BadWeights::MalformedWeight(line)| {
+                BadParameters::BadWeights(BadWeights::MalformedWeight(weights_start + line))
+            })?;
+    Ok((header, weights))
+}
+
+/// Read the number of bowls from a header value.
+fn bowls(value: &str) -> Result<usize, BadParameters> {
+    let bowls = value
+        .parse::<usize>()
+        .map_err(|_| BadParameters::MalformedHeader)?;
+    if bowls > MAX_BOWLS {
+        Err(BadParameters::TooManyBowls)
+    } else {
+        Ok(bowls)
+    }
+}
+
+/// 1 when `Red` is to move, -1 when `Blue` is.
+///
+/// Multiplying a value for the player to move by this sign gives the value for `Red`.
+fn sign(player: Player) -> f64 {
+    match player {
+        Player::Red => 1.0,
+        Player::Blue => -1.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Position;
+    use crate::learning::network::NetworkBuilder;
+
+    fn temporary(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("{}-{}.txt", name, std::process::id()))
+    }
+
+    #[test]
+    fn headers_precede_weights() {
+        let parameters = "# model\nbowls 3\n\nlayers 8 4\n0.5\n# comment\n-1\n";
+
+        let (header, weights) =
+            read(parameters, &["bowls", "layers"]).expect("parameters to be read");
+
+        assert_eq!(header, vec!["3", "8 4"]);
+        assert_eq!(weights, Weights::new(vec![0.5, -1.0]));
+    }
+
+    #[test]
+    fn bad_parameters_are_rejected() {
+        assert_eq!(
+            read("1\n2\n", &["bowls"]).err(),
+            Some(BadParameters::MalformedHeader)
+        );
+        assert_eq!(
+            read("bowlsy 1\n", &["bowls"]).err(),
+            Some(BadParameters::MalformedHeader)
+        );
+        assert_eq!(
+            read("# td\nbowls 1\nheavy\n", &["bowls"]).err(),
+            Some(BadParameters::BadWeights(BadWeights::MalformedWeight(3)))
+        );
+        assert_eq!(bowls("three").err(), Some(BadParameters::MalformedHeader));
+        assert_eq!(bowls("256").err(), Some(BadParameters::TooManyBowls));
+        assert_eq!(
+            load("/nonexistent/parameters.txt").err(),
+            Some(BadParameters::Unreadable(io::ErrorKind::NotFound))
+        );
+    }
+
+    #[test]
+    fn parameters_survive_a_round_trip() {
+        let mut agent = TemporalDifference::strategy().bowls(3).seeded(7).build();
+        agent.train(&Position::new(3, 2), 20);
+        let path = temporary("td");
+
+        agent.save(&path).expect("parameters to be saved");
+        let loaded = TemporalDifference::load(&path).expect("parameters to be loaded");
+        fs::remove_file(&path).expect("file to be removed");
+
+        assert_eq!(loaded.to_string(), agent.to_string());
+    }
+
+    #[test]
+    fn networks_survive_a_round_trip() {
+        let network = NetworkBuilder::new()
+            .bowls(3)
+            .hidden(&[8])
+            .seeded(7)
+            .build();
+        let path = temporary("network");
+
+        network.save(&path).expect("parameters to be saved");
+        let loaded = Network::load(&path).expect("parameters to be loaded");
+        fs::remove_file(&path).expect("file to be removed");
+
+        assert_eq!(loaded, network);
+    }
+
+    #[test]
+    fn weights_must_match_the_model() {
+        assert_eq!(
+            TemporalDifference::from_parameters("bowls 1\n1\n").err(),
+            Some(BadParameters::WrongNumberOfWeights)
+        );
+        assert_eq!(
+            Network::from_parameters("bowls 1\nlayers 4\n1\n").err(),
+            Some(BadParameters::WrongNumberOfWeights)
+        );
+    }
+}
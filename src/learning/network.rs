@@ -0,0 +1,784 @@
+//! A multilayer perceptron that values positions and the plays in them.
+//!
+//! A `Network` reads a position from the perspective of the player to move. Every pit holds a fraction of all the
+//! stones in the game. The pits pass through fully connected hidden layers with rectified linear units, and end in two
+//! heads.
+//!
+//! * The value head estimates the score of the player to move, as a fraction of all stones, between -1 and 1.
+//! * The policy head estimates how promising each bowl of the player to move is. The priors are a softmax over the
+//!   options.
+//!
+//! The value makes a network a `Heuristic`, the priors make it a `Prior`.
+//!
+//! ```
+//! use mancala::game::Position;
+//! use mancala::learning::network::{Adam, Network, NetworkBuilder, Sample};
+//! use mancala::strategy::tree::{Depth, MonteCarlo};
+//! use mancala::strategy::AlphaBeta;
+//!
+//! let mut network = NetworkBuilder::new().bowls(3).hidden(&[16]).seeded(7).build();
+//! let samples = vec![Sample {
+//!     position: Position::new(3, 2),
+//!     value: 0.25,
+//!     policy: vec![0.0, 1.0, 0.0],
+//! }];
+//! let mut optimizer = Adam::new(0.01);
+//! for _ in 0..10 {
+//!     network.train(&samples, &mut optimizer);
+//! }
+//!
+//! let parameters = network.to_string();
+//! let mut alpha_beta = AlphaBeta::strategy().limited_to(Depth::Limit(3)).with_heuristic(network).build();
+//! let prior: Network = parameters.parse().expect("parameters to be read back");
+//! let mut monte_carlo = MonteCarlo::strategy().with_prior(prior).build();
+//! ```
+//!
+//! Training minimizes the squared error of the value plus the cross entropy of the priors, with an `Optimizer` like
+//! `Sgd` or `Adam`. Samples can be taken from finished games with `Sample::from_game`.
+//!
+//! The parameters are written as a header with the number of bowls and the sizes of the input and hidden layers,
+//! followed by the weights in the format of `tuning::Weights`.
+//!
+//! ```text
+//! # mlp value and policy network
+//! bowls 6
+//! layers 14 32
+//! 0.25
+//! -0.125
+//! ...
+//! ```
+
+use super::{bowls, load, read, BadParameters};
+use crate::{
+    game::{Game, Pit, Player, Position, MAX_BOWLS},
+    strategy::{Heuristic, Prior, Value},
+    tuning::Weights,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Build Networks
+pub struct NetworkBuilder {
+    bowls: usize,
+    hidden: Vec<usize>,
+    seed: Option<u64>,
+}
+
+impl NetworkBuilder {
+    /// Creates a new NetworkBuilder
+    ///
+    /// The default number of bowls is 6, with a single hidden layer of 32 units.
+    pub fn new() -> Self {
+        NetworkBuilder {
+            bowls: 6,
+            hidden: vec![32],
+            seed: None,
+        }
+    }
+
+    /// for positions with a certain number of bowls
+    pub fn bowls(mut self, bowls: usize) -> Self {
+        self.bowls = bowls;
+        self
+    }
+
+    /// with hidden layers of certain sizes
+    pub fn hidden(mut self, hidden: &[usize]) -> Self {
+        self.hidden = hidden.to_vec();
+        self
+    }
+
+    /// with a seed, so that the initial weights can be repeated
+    pub fn seeded(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Build a network with random weights
+    ///
+    /// Panics if there are more than `MAX_BOWLS` bowls.
+    pub fn build(self) -> Network {
+        assert!(self.bowls <= MAX_BOWLS, "at most {} bowls", MAX_BOWLS);
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let mut network = Network::shaped(self.bowls, self.hidden);
+        for layer in network.layers() {
+            let limit = (6.0 / (layer.inputs + layer.outputs) as f64).sqrt();
+            for output in 0..layer.outputs {
+                for input in 0..layer.inputs {
+                    network.parameters[layer.weight(output, input)] = rng.gen_range(-limit..limit);
+                }
+            }
+        }
+        network
+    }
+}
+
+impl Default for NetworkBuilder {
+    fn default() -> Self {
+        NetworkBuilder::new()
+    }
+}
+
+/// A fully connected layer, with its weights and biases at `offset` in the parameters.
+#[derive(Debug, Copy, Clone)]
+struct Layer {
+    inputs: usize,
+    outputs: usize,
+    offset: usize,
+}
+
+impl Layer {
+    fn weight(&self, output: usize, input: usize) -> usize {
+        self.offset + output * self.inputs + input
+    }
+
+    fn bias(&self, output: usize) -> usize {
+        self.offset + self.outputs * self.inputs + output
+    }
+
+    fn size(&self) -> usize {
+        self.outputs * (self.inputs + 1)
+    }
+
+    fn apply(&self, parameters: &[f64], input: &[f64]) -> Vec<f64> {
+        (0..self.outputs)
+            .map(|output| {
+                let weights = &parameters[self.weight(output, 0)..self.weight(output, self.inputs)];
+                let sum: f64 = weights.iter().zip(input).map(|(w, x)| w * x).sum();
+                sum + parameters[self.bias(output)]
+            })
+            .collect()
+    }
+}
+
+/// A value and policy network.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Network {
+    bowls: usize,
+    hidden: Vec<usize>,
+    parameters: Vec<f64>,
+}
+
+/// What a network thinks of a position.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Prediction {
+    /// The estimated score of the player to move, as a fraction of all stones
+    pub value: f64,
+    /// The prior of every bowl of the player to move
+    pub priors: Vec<f64>,
+}
+
+/// What a network should think of a position.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Sample {
+    /// The position
+    pub position: Position,
+    /// The score of the player to move, as a fraction of all stones
+    pub value: f64,
+    /// The probability of every bowl of the player to move
+    pub policy: Vec<f64>,
+}
+
+impl Sample {
+    /// The samples of every play of a finished game.
+    ///
+    /// Each sample values its position by the final score, and has the bowl played as policy. An unfinished game has
+    /// no samples.
+    pub fn from_game(game: &Game) -> Vec<Sample> {
+        let outcome = match game.outcome() {
+            Some(outcome) => outcome,
+            None => return vec![],
+        };
+        game.positions()
+            .zip(game.history())
+            .map(|(position, (player, bowl))| {
+                let margin = if *player == Player::Red {
+                    outcome.margin()
+                } else {
+                    -outcome.margin()
+                };
+                let mut policy = vec![0.0; position.size()];
+                policy[*bowl] = 1.0;
+                Sample {
+                    position,
+                    value: margin as f64 / total(&position),
+                    policy,
+                }
+            })
+            .collect()
+    }
+}
+
+/// The loss of a network on samples.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Loss {
+    /// The mean squared error of the value
+    pub value: f64,
+    /// The mean cross entropy of the priors
+    pub policy: f64,
+}
+
+impl Loss {
+    /// The sum of the value loss and the policy loss.
+    pub fn total(&self) -> f64 {
+        self.value + self.policy
+    }
+}
+
+impl Display for Loss {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "loss {:.4} (value {:.4}, policy {:.4})",
+            self.total(),
+            self.value,
+            self.policy
+        )
+    }
+}
+
+/// The activations of a network for a single position.
+struct Activations {
+    /// The input, followed by the output of every hidden layer
+    layers: Vec<Vec<f64>>,
+    value: f64,
+    priors: Vec<f64>,
+}
+
+impl Network {
+    fn shaped(bowls: usize, hidden: Vec<usize>) -> Self {
+        let mut network = Network {
+            bowls,
+            hidden,
+            parameters: vec![],
+        };
+        let size = network.layers().iter().map(Layer::size).sum();
+        network.parameters = vec![0.0; size];
+        network
+    }
+
+    /// The hidden layers, followed by the value head and the policy head.
+    fn layers(&self) -> Vec<Layer> {
+        let mut layers = vec![];
+        let mut inputs = 2 * self.bowls + 2;
+        let mut offset = 0;
+        let heads = [1, self.bowls];
+        for outputs in self.hidden.iter().chain(&heads) {
+            let layer = Layer {
+                inputs,
+                outputs: *outputs,
+                offset,
+            };
+            offset += layer.size();
+            if layers.len() < self.hidden.len() {
+                inputs = *outputs;
+            }
+            layers.push(layer);
+        }
+        layers
+    }
+
+    /// Read a network from its parameters.
+    pub fn from_parameters(parameters: &str) -> Result<Self, BadParameters> {
+        let (header, weights) = read(parameters, &["bowls", "layers"])?;
+        let bowls = bowls(header[0])?;
+        let layers = header[1]
+            .split_whitespace()
+            .map(str::parse::<usize>)
+            .collect::<Result<Vec<usize>, _>>()
+            .map_err(|_| BadParameters::MalformedHeader)?;
+        if layers.first() != Some(&(2 * bowls + 2)) {
+            return Err(BadParameters::MalformedHeader);
+        }
+        let mut network = Network::shaped(bowls, layers[1..].to_vec());
+        if weights.values().len() != network.parameters.len() {
+            return Err(BadParameters::WrongNumberOfWeights);
+        }
+        network.parameters = weights.values().to_vec();
+        Ok(network)
+    }
+
+    /// Load a network from a file with its parameters.
+    pub fn load<P>(path: P) -> Result<Self, BadParameters>
+    where
+        P: AsRef<Path>,
+    {
+        Network::from_parameters(&load(path)?)
+    }
+
+    /// Save the parameters of this network to a file.
+    pub fn save<P>(&self, path: P) -> io::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        fs::write(path, self.to_string())
+    }
+
+    /// The number of bowls this network is for.
+    pub fn bowls(&self) -> usize {
+        self.bowls
+    }
+
+    /// What this network thinks of `position`.
+    ///
+    /// Panics if `position` does not have the number of bowls of this network.
+    pub fn predict(&self, position: &Position) -> Prediction {
+        let activations = self.forward(position);
+        Prediction {
+            value: activations.value,
+            priors: activations.priors,
+        }
+    }
+
+    fn forward(&self, position: &Position) -> Activations {
+        assert_eq!(
+            position.size(),
+            self.bowls,
+            "network for {} bowls",
+            self.bowls
+        );
+        let layers = self.layers();
+        let (hidden, heads) = layers.split_at(self.hidden.len());
+        let mut activations = vec![encode(position)];
+        for layer in hidden {
+            let input = activations.last().expect("an input");
+            let output = layer
+                .apply(&self.parameters, input)
+                .into_iter()
+                .map(|x| x.max(0.0))
+                .collect();
+            activations.push(output);
+        }
+        let last = activations.last().expect("an input");
+        let value = heads[0].apply(&self.parameters, last)[0].tanh();
+        let logits = heads[1].apply(&self.parameters, last);
+        Activations {
+            layers: activations,
+            value,
+            priors: softmax(position, &logits),
+        }
+    }
+
+    /// The loss on `samples`, without training.
+    pub fn loss(&self, samples: &[Sample]) -> Loss {
+        self.gradient(samples).1
+    }
+
+    /// Take a single step with `optimizer` to lower the loss on `samples`.
+    ///
+    /// Returns the loss before the step.
+    pub fn train<O>(&mut self, samples: &[Sample], optimizer: &mut O) -> Loss
+    where
+        O: Optimizer,
+    {
+        let (gradient, loss) = self.gradient(samples);
+        optimizer.step(&mut self.parameters, &gradient);
+        loss
+    }
+
+    /// The gradient of the mean loss on `samples` with respect to the parameters, and the mean loss.
+    fn gradient(&self, samples: &[Sample]) -> (Vec<f64>, Loss) {
+        let layers = self.layers();
+        let (hidden, heads) = layers.split_at(self.hidden.len());
+        let mut gradient = vec![0.0; self.parameters.len()];
+        let mut loss = Loss {
+            value: 0.0,
+            policy: 0.0,
+        };
+        for sample in samples {
+            let activations = self.forward(&sample.position);
+            let last = activations.layers.last().expect("an input");
+            let mut errors = vec![0.0; last.len()];
+
+            let error = activations.value - sample.value;
+            loss.value += error * error;
+            let value_error = 2.0 * error * (1.0 - activations.value * activations.value);
+            backward(
+                &heads[0],
+                &self.parameters,
+                last,
+                &[value_error],
+                &mut gradient,
+                &mut errors,
+            );
+
+            let options = sample.position.options();
+            let mut policy_errors = vec![0.0; self.bowls];
+            for bowl in options {
+                let (prior, target) = (activations.priors[bowl], sample.policy[bowl]);
+                if target > 0.0 {
+                    loss.policy -= target * prior.max(f64::MIN_POSITIVE).ln();
+                }
+                policy_errors[bowl] = prior - target;
+            }
+            backward(
+                &heads[1],
+                &self.parameters,
+                last,
+                &policy_errors,
+                &mut gradient,
+                &mut errors,
+            );
+
+            for (index, layer) in hidden.iter().enumerate().rev() {
+                let output = &activations.layers[index + 1];
+                let layer_errors: Vec<f64> = errors
+                    .iter()
+                    .zip(output)
+                    .map(|(error, x)| if *x > 0.0 { *error } else { 0.0 })
+                    .collect();
+                errors = vec![0.0; layer.inputs];
+                let input = &activations.layers[index];
+                backward(
+                    layer,
+                    &self.parameters,
+                    input,
+                    &layer_errors,
+                    &mut gradient,
+                    &mut errors,
+                );
+            }
+        }
+        let count = samples.len().max(1) as f64;
+        for partial in gradient.iter_mut() {
+            *partial /= count;
+        }
+        loss.value /= count;
+        loss.policy /= count;
+        (gradient, loss)
+    }
+}
+
+/// Accumulate the gradient of `layer` with respect to its parameters, given the `errors` of its outputs, and the
+/// errors of its `input` in `input_errors`.
+fn backward(
+    layer: &Layer,
+    parameters: &[f64],
+    input: &[f64],
+    errors: &[f64],
+    gradient: &mut [f64],
+    input_errors: &mut [f64],
+) {
+    for (output, error) in errors
+        .iter()
+        .enumerate()
+        .filter(|(_, error)| **error != 0.0)
+    {
+        for (index, x) in input.iter().enumerate() {
+            gradient[layer.weight(output, index)] += error * x;
+            input_errors[index] += error * parameters[layer.weight(output, index)];
+        }
+        gradient[layer.bias(output)] += error;
+    }
+}
+
+/// The total number of stones in `position`.
+pub(crate) fn total(position: &Position) -> f64 {
+    (position.total() as f64).max(1.0)
+}
+
+/// Every pit from the perspective of the player to move, as a fraction of all stones.
+fn encode(position: &Position) -> Vec<f64> {
+    let total = total(position);
+    (0..2 * position.size())
+        .map(Pit::Bowl)
+        .chain(vec![Pit::Store, Pit::OpponentStore])
+        .map(|pit| position.stones(pit) as f64 / total)
+        .collect()
+}
+
+/// The softmax of `logits` over the options of `position`, 0 for other bowls.
+fn softmax(position: &Position, logits: &[f64]) -> Vec<f64> {
+    let options = position.options();
+    let mut priors = vec![0.0; logits.len()];
+    let maximum = options
+        .iter()
+        .map(|bowl| logits[*bowl])
+        .fold(f64::NEG_INFINITY, f64::max);
+    let mut sum = 0.0;
+    for bowl in &options {
+        priors[*bowl] = (logits[*bowl] - maximum).exp();
+        sum += priors[*bowl];
+    }
+    for bowl in &options {
+        priors[*bowl] /= sum;
+    }
+    priors
+}
+
+impl Heuristic for Network {
+    fn evaluate(&self, position: &Position) -> Value {
+        match position.score() {
+            Some(score) => Value::Actual(score),
+            None => Value::estimate(self.predict(position).value * total(position)),
+        }
+    }
+}
+
+impl Prior for Network {
+    fn priors(&self, position: &Position) -> Vec<f64> {
+        self.predict(position).priors
+    }
+}
+
+impl Display for Network {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "# mlp value and policy network")?;
+        writeln!(f, "bowls {}", self.bowls)?;
+        write!(f, "layers {}", 2 * self.bowls + 2)?;
+        for size in &self.hidden {
+            write!(f, " {}", size)?;
+        }
+        writeln!(f)?;
+        write!(f, "{}", Weights::new(self.parameters.clone()))
+    }
+}
+
+impl FromStr for Network {
+    type Err = BadParameters;
+
+    fn from_str(parameters: &str) -> Result<Self, Self::Err> {
+        Network::from_parameters(parameters)
+    }
+}
+
+/// A way to change parameters along their gradient.
+pub trait Optimizer {
+    /// Change `parameters` to lower the loss, given its `gradient`.
+    fn step(&mut self, parameters: &mut [f64], gradient: &[f64]);
+}
+
+/// Stochastic gradient descent, with momentum.
+pub struct Sgd {
+    learning_rate: f64,
+    momentum: f64,
+    velocity: Vec<f64>,
+}
+
+impl Sgd {
+    /// Create stochastic gradient descent with a certain learning rate, without momentum.
+    pub fn new(learning_rate: f64) -> Self {
+        Sgd {
+            learning_rate,
+            momentum: 0.0,
+            velocity: vec![],
+        }
+    }
+
+    /// with a certain momentum
+    pub fn with_momentum(mut self, momentum: f64) -> Self {
+        self.momentum = momentum;
+        self
+    }
+}
+
+impl Optimizer for Sgd {
+    fn step(&mut self, parameters: &mut [f64], gradient: &[f64]) {
+        self.velocity.resize(parameters.len(), 0.0);
+        for ((parameter, partial), velocity) in parameters
+            .iter_mut()
+            .zip(gradient)
+            .zip(self.velocity.iter_mut())
+        {
+            *velocity = self.momentum * *velocity - self.learning_rate * partial;
+            *parameter += *velocity;
+        }
+    }
+}
+
+/// [Adam](https://arxiv.org/abs/1412.6980), gradient descent with adaptive estimates of the moments of the gradient.
+pub struct Adam {
+    learning_rate: f64,
+    beta1: f64,
+    beta2: f64,
+    epsilon: f64,
+    steps: i32,
+    first: Vec<f64>,
+    second: Vec<f64>,
+}
+
+impl Adam {
+    /// Create Adam with a certain learning rate, and the usual decay rates of 0.9 and 0.999.
+    pub fn new(learning_rate: f64) -> Self {
+        Adam {
+            learning_rate,
+            beta1: 0.9,
+            beta2: 0.999,
+            epsilon: 1e-8,
+            steps: 0,
+            first: vec![],
+            second: vec![],
+        }
+    }
+}
+
+impl Optimizer for Adam {
+    fn step(&mut self, parameters: &mut [f64], gradient: &[f64]) {
+        self.first.resize(parameters.len(), 0.0);
+        self.second.resize(parameters.len(), 0.0);
+        self.steps = self.steps.saturating_add(1);
+        let first_correction = 1.0 - self.beta1.powi(self.steps);
+        let second_correction = 1.0 - self.beta2.powi(self.steps);
+        for (index, (parameter, partial)) in parameters.iter_mut().zip(gradient).enumerate() {
+            let first = &mut self.first[index];
+            let second = &mut self.second[index];
+            *first = self.beta1 * *first + (1.0 - self.beta1) * partial;
+            *second = self.beta2 * *second + (1.0 - self.beta2) * partial * partial;
+            let first = *first / first_correction;
+            let second = *second / second_correction;
+            *parameter -= self.learning_rate * first / (second.sqrt() + self.epsilon);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bout::Bout;
+    use crate::game::GameBuilder;
+    use crate::strategy::tree::{Depth, MonteCarlo};
+    use crate::strategy::{AlphaBeta, Strategy};
+
+    fn network() -> Network {
+        NetworkBuilder::new()
+            .bowls(3)
+            .hidden(&[8, 6])
+            .seeded(7)
+            .build()
+    }
+
+    fn samples() -> Vec<Sample> {
+        let mut red = AlphaBeta::strategy().limited_to(Depth::Limit(2)).build();
+        let mut blue = AlphaBeta::strategy().limited_to(Depth::Limit(1)).build();
+        let game = Bout::new(&mut red, &mut blue, &|_| {})
+            .start(
+                GameBuilder::new()
+                    .bowls(3)
+                    .stones(3)
+                    .build()
+                    .expect("a playable game"),
+            )
+            .expect("a finished game");
+        Sample::from_game(&game)
+    }
+
+    #[test]
+    fn priors_spread_over_options() {
+        let position = Position::from([1, 0, 2, 0, 1, 1]);
+
+        let priors = network().priors(&position);
+
+        assert_eq!(priors[1], 0.0);
+        assert!((priors.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn finished_positions_are_valued_exactly() {
+        let position = Position::from((1, 4, [0, 0, 0, 3]));
+        let network = NetworkBuilder::new().bowls(2).seeded(7).build();
+
+        assert_eq!(network.evaluate(&position), Value::Actual(-6));
+    }
+
+    #[test]
+    fn samples_follow_the_game() {
+        let samples = samples();
+        let first = &samples[0];
+
+        assert_eq!(first.position, Position::new(3, 3));
+        assert_eq!(first.policy.iter().sum::<f64>(), 1.0);
+        assert!(samples.iter().all(|sample| sample.value.abs() <= 1.0));
+    }
+
+    #[test]
+    fn gradient_matches_finite_differences() {
+        let network = network();
+        let samples = samples();
+        let (gradient, _) = network.gradient(&samples);
+        let step = 1e-6;
+
+        for index in (0..network.parameters.len()).step_by(7) {
+            let mut higher = network.clone();
+            higher.parameters[index] += step;
+            let mut lower = network.clone();
+            lower.parameters[index] -= step;
+            let estimate =
+                (higher.loss(&samples).total() - lower.loss(&samples).total()) / (2.0 * step);
+
+            assert!(
+                (estimate - gradient[index]).abs() < 1e-4,
+                "parameter {}: {} != {}",
+                index,
+                estimate,
+                gradient[index]
+            );
+        }
+    }
+
+    #[test]
+    fn adam_lowers_the_loss() {
+        let mut network = network();
+        let samples = samples();
+        let mut optimizer = Adam::new(0.01);
+
+        let before = network.loss(&samples).total();
+        for _ in 0..200 {
+            network.train(&samples, &mut optimizer);
+        }
+
+        assert!(network.loss(&samples).total() < before);
+    }
+
+    #[test]
+    fn sgd_lowers_the_loss() {
+        let mut network = network();
+        let samples = samples();
+        let mut optimizer = Sgd::new(0.05).with_momentum(0.9);
+
+        let before = network.loss(&samples).total();
+        for _ in 0..200 {
+            network.train(&samples, &mut optimizer);
+        }
+
+        assert!(network.loss(&samples).total() < before);
+    }
+
+    #[test]
+    fn layers_must_fit_the_bowls() {
+        assert_eq!(
+            Network::from_parameters("bowls 1\nlayers 3\n").err(),
+            Some(BadParameters::MalformedHeader)
+        );
+        assert_eq!(
+            Network::from_parameters("bowls 1\nlayers 4 two\n").err(),
+            Some(BadParameters::MalformedHeader)
+        );
+    }
+
+    #[test]
+    fn networks_plug_into_tree_searches() {
+        let position = Position::new(3, 3);
+        let mut alpha_beta = AlphaBeta::strategy()
+            .limited_to(Depth::Limit(3))
+            .with_heuristic(network())
+            .build();
+        let mut monte_carlo = MonteCarlo::strategy()
+            .simulations(100)
+            .with_prior(network())
+            .seeded(7)
+            .build();
+
+        for bowl in [alpha_beta.play(&position), monte_carlo.play(&position)] {
+            assert!(bowl
+                .map(|bowl| position.options().contains(&bowl))
+                .unwrap_or(false));
+        }
+    }
+}
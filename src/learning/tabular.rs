@@ -0,0 +1,438 @@
+//! Tabular learning of the value of plays.
+//!
+//! On small boards, there are few enough positions to learn the value of every play in every position exactly.
+//! `QLearning` keeps a table of these values, keyed on the hash key of a position, and learns them by playing against
+//! itself. It updates either by [Q-learning](https://en.wikipedia.org/wiki/Q-learning), which learns from the best
+//! next play, or by [SARSA](https://en.wikipedia.org/wiki/State%E2%80%93action%E2%80%93reward%E2%80%93state%E2%80%93action),
+//! which learns from the next play it actually makes.
+//!
+//! Both the exploration and the learning rate follow a `Schedule` over the episodes. Once trained, the greedy plays
+//! can be checked against `MinMax` in every position that can be reached.
+//!
+//! ```
+//! use mancala::game::Position;
+//! use mancala::learning::tabular::{QLearning, Schedule};
+//!
+//! let start = Position::new(2, 2);
+//! let mut agent = QLearning::strategy()
+//!     .exploring(Schedule::Linear { start: 1.0, end: 0.1, episodes: 2000 })
+//!     .learning_rate(Schedule::Constant(0.5))
+//!     .seeded(7)
+//!     .build();
+//! agent.train(&start, 2000);
+//!
+//! println!("{}", agent.convergence(&start));
+//! ```
+
+use crate::{
+    game::{Bowl, Position},
+    strategy::{tree::Depth, Analyse, MinMax, Strategy},
+};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+
+/// The way the value of a play is updated.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Update {
+    /// Learn from the best next play.
+    QLearning,
+    /// Learn from the next play that is made, exploration included.
+    Sarsa,
+}
+
+/// A value that changes with the number of episodes played.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Schedule {
+    /// The same value for every episode.
+    Constant(f64),
+    /// Change linearly from `start` to `end` over a number of `episodes`, and stay at `end` afterwards.
+    Linear {
+        /// The value for the first episode
+        start: f64,
+        /// The value after `episodes` episodes
+        end: f64,
+        /// The number of episodes to go from `start` to `end`
+        episodes: usize,
+    },
+    /// Multiply `start` by `decay` every episode, without going below `minimum`.
+    Exponential {
+        /// The value for the first episode
+        start: f64,
+        /// The factor with which the value changes every episode
+        decay: f64,
+        /// The lowest value
+        minimum: f64,
+    },
+}
+
+impl Schedule {
+    /// The value for the episode with index `episode`, counting from 0.
+    pub fn at(&self, episode: usize) -> f64 {
+        match *self {
+            Schedule::Constant(value) => value,
+            Schedule::Linear {
+                start,
+                end,
+                episodes,
+            } => {
+                if episode >= episodes {
+                    end
+                } else {
+                    start + (end - start) * episode as f64 / episodes as f64
+                }
+            }
+            Schedule::Exponential {
+                start,
+                decay,
+                minimum,
+            } => (start * decay.powf(episode as f64)).max(minimum),
+        }
+    }
+}
+
+/// Build QLearning strategy instances
+pub struct QLearningBuilder {
+    update: Update,
+    exploration: Schedule,
+    learning_rate: Schedule,
+    seed: Option<u64>,
+}
+
+impl QLearningBuilder {
+    /// Build an untrained QLearning strategy
+    pub fn build(self) -> QLearning {
+        let rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        QLearning {
+            table: HashMap::new(),
+            update: self.update,
+            exploration: self.exploration,
+            learning_rate: self.learning_rate,
+            episodes: 0,
+            rng,
+        }
+    }
+
+    /// updating in a certain way
+    pub fn updating(mut self, update: Update) -> Self {
+        self.update = update;
+        self
+    }
+
+    /// exploring with a random play with a probability that follows a schedule
+    pub fn exploring(mut self, exploration: Schedule) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    /// with a learning rate that follows a schedule
+    pub fn learning_rate(mut self, learning_rate: Schedule) -> Self {
+        self.learning_rate = learning_rate;
+        self
+    }
+
+    /// with a seed, so that training can be repeated
+    pub fn seeded(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+}
+
+/// Learn the value of every play in a table, and play greedily by it.
+pub struct QLearning {
+    table: HashMap<u64, Vec<f64>>,
+    update: Update,
+    exploration: Schedule,
+    learning_rate: Schedule,
+    episodes: usize,
+    rng: StdRng,
+}
+
+impl QLearning {
+    /// Create a default QLearningBuilder
+    ///
+    /// It uses Q-learning, with an exploration that goes from 1 to 0.1 over 10000 episodes and a learning rate of 0.5.
+    pub fn strategy() -> QLearningBuilder {
+        QLearningBuilder {
+            update: Update::QLearning,
+            exploration: Schedule::Linear {
+                start: 1.0,
+                end: 0.1,
+                episodes: 10_000,
+            },
+            learning_rate: Schedule::Constant(0.5),
+            seed: None,
+        }
+    }
+
+    /// The number of episodes this strategy trained for.
+    pub fn episodes(&self) -> usize {
+        self.episodes
+    }
+
+    /// The number of positions in the table.
+    pub fn positions(&self) -> usize {
+        self.table.len()
+    }
+
+    /// The learned value of playing `bowl` in `position`, for the player to move.
+    ///
+    /// Plays that were never made are worth 0.
+    pub fn value(&self, position: &Position, bowl: Bowl) -> f64 {
+        self.table
+            .get(&position.hash_key())
+            .and_then(|values| values.get(bowl))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Train by playing `episodes` games against itself, starting from `start`.
+    ///
+    /// Returns the average score for `Red`.
+    pub fn train(&mut self, start: &Position, episodes: usize) -> f64 {
+        let total: f64 = (0..episodes).map(|_| self.episode(start)).sum();
+        if episodes == 0 {
+            0.0
+        } else {
+            total / episodes as f64
+        }
+    }
+
+    fn episode(&mut self, start: &Position) -> f64 {
+        let exploration = self.exploration.at(self.episodes);
+        let learning_rate = self.learning_rate.at(self.episodes);
+        let mut position = *start;
+        let mut bowl = self.explore(&position, exploration);
+        while let Some(played) = bowl {
+            let after = position.play(played).expect("option to be playable");
+            let (target, next) = match after.score_for(position.turn()) {
+                Some(score) => (score as f64, None),
+                None => {
+                    let next = self.explore(&after, exploration);
+                    let value = match (self.update, next) {
+                        (Update::Sarsa, Some(next)) => self.value(&after, next),
+                        _ => self.best(&after).map(|(_, value)| value).unwrap_or(0.0),
+                    };
+                    let target = if after.turn() == position.turn() {
+                        value
+                    } else {
+                        -value
+                    };
+                    (target, next)
+                }
+            };
+            let values = self
+                .table
+                .entry(position.hash_key())
+                .or_insert_with(|| vec![0.0; position.size()]);
+            values[played] += learning_rate * (target - values[played]);
+            position = after;
+            bowl = next;
+        }
+        self.episodes += 1;
+        position
+            .outcome()
+            .map(|outcome| outcome.margin() as f64)
+            .unwrap_or(0.0)
+    }
+
+    fn explore(&mut self, position: &Position, exploration: f64) -> Option<Bowl> {
+        if self.rng.gen::<f64>() < exploration {
+            position.options().choose(&mut self.rng).copied()
+        } else {
+            self.best(position).map(|(bowl, _)| bowl)
+        }
+    }
+
+    /// The option with the highest value, the first one in case of a tie.
+    fn best(&self, position: &Position) -> Option<(Bowl, f64)> {
+        let mut best: Option<(Bowl, f64)> = None;
+        for bowl in position.options() {
+            let value = self.value(position, bowl);
+            if best.map(|(_, best)| value > best).unwrap_or(true) {
+                best = Some((bowl, value));
+            }
+        }
+        best
+    }
+
+    /// Compare the greedy plays with the plays of `MinMax`, in every unfinished position reachable from `start`.
+    ///
+    /// A greedy play is optimal when it is worth as much as the best play.
+    pub fn convergence(&self, start: &Position) -> Convergence {
+        let mut minmax = MinMax::new();
+        let mut convergence = Convergence {
+            positions: 0,
+            optimal: 0,
+        };
+        for position in reachable(start) {
+            let analysis = minmax.analyse(&position, &Depth::Infinite, 0);
+            let best = analysis.best().map(|line| line.value);
+            let greedy = self.best(&position).and_then(|(bowl, _)| {
+                analysis
+                    .lines()
+                    .iter()
+                    .find(|line| line.bowl == bowl)
+                    .map(|line| line.value)
+            });
+            convergence.positions += 1;
+            if greedy.is_some() && greedy == best {
+                convergence.optimal += 1;
+            }
+        }
+        convergence
+    }
+}
+
+impl Strategy for QLearning {
+    fn play(&mut self, position: &Position) -> Option<Bowl> {
+        self.best(position).map(|(bowl, _)| bowl)
+    }
+}
+
+/// The unfinished positions that can be reached from `start`, `start` included.
+fn reachable(start: &Position) -> Vec<Position> {
+    let mut seen = HashSet::new();
+    let mut positions = vec![];
+    let mut stack = vec![*start];
+    while let Some(position) = stack.pop() {
+        if position.finished() || !seen.insert(position.hash_key()) {
+            continue;
+        }
+        for bowl in position.options() {
+            stack.push(position.play(bowl).expect("option to be playable"));
+        }
+        positions.push(position);
+    }
+    positions
+}
+
+/// How many positions the greedy plays of a `QLearning` strategy are optimal in.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Convergence {
+    /// The number of positions compared
+    pub positions: usize,
+    /// The number of positions in which the greedy play is optimal
+    pub optimal: usize,
+}
+
+impl Convergence {
+    /// The fraction of positions in which the greedy play is optimal.
+    pub fn rate(&self) -> f64 {
+        if self.positions == 0 {
+            1.0
+        } else {
+            self.optimal as f64 / self.positions as f64
+        }
+    }
+
+    /// Determine if the greedy play is optimal in every position.
+    pub fn converged(&self) -> bool {
+        self.optimal == self.positions
+    }
+}
+
+impl Display for Convergence {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "optimal in {} of {} positions ({:.1}%)",
+            self.optimal,
+            self.positions,
+            100.0 * self.rate()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent(update: Update) -> QLearningBuilder {
+        QLearning::strategy()
+            .updating(update)
+            .exploring(Schedule::Linear {
+                start: 1.0,
+                end: 0.2,
+                episodes: 20_000,
+            })
+            .learning_rate(Schedule::Exponential {
+                start: 1.0,
+                decay: 0.9999,
+                minimum: 0.2,
+            })
+            .seeded(7)
+    }
+
+    #[test]
+    fn schedules_change_with_episodes() {
+        let linear = Schedule::Linear {
+            start: 1.0,
+            end: 0.0,
+            episodes: 4,
+        };
+        let exponential = Schedule::Exponential {
+            start: 1.0,
+            decay: 0.5,
+            minimum: 0.2,
+        };
+
+        assert_eq!(Schedule::Constant(0.3).at(100), 0.3);
+        assert_eq!(linear.at(0), 1.0);
+        assert_eq!(linear.at(2), 0.5);
+        assert_eq!(linear.at(10), 0.0);
+        assert_eq!(exponential.at(1), 0.5);
+        assert_eq!(exponential.at(5), 0.2);
+    }
+
+    #[test]
+    fn reachable_positions_are_unfinished_and_distinct() {
+        let positions = reachable(&Position::new(2, 2));
+        let keys: HashSet<u64> = positions.iter().map(Position::hash_key).collect();
+
+        assert_eq!(keys.len(), positions.len());
+        assert!(positions.iter().all(|position| !position.finished()));
+        assert!(positions.contains(&Position::new(2, 2)));
+    }
+
+    #[test]
+    fn training_counts_episodes() {
+        let mut agent = agent(Update::QLearning).build();
+
+        agent.train(&Position::new(2, 1), 10);
+
+        assert_eq!(agent.episodes(), 10);
+        assert!(agent.positions() > 0);
+    }
+
+    #[test]
+    fn q_learning_converges_to_minmax() {
+        let start = Position::new(2, 2);
+        let mut agent = agent(Update::QLearning).build();
+
+        agent.train(&start, 20_000);
+
+        let convergence = agent.convergence(&start);
+        assert!(convergence.converged(), "{}", convergence);
+    }
+
+    #[test]
+    fn sarsa_converges_to_minmax() {
+        let start = Position::new(2, 2);
+        let mut agent = agent(Update::Sarsa)
+            .exploring(Schedule::Linear {
+                start: 1.0,
+                end: 0.0,
+                episodes: 20_000,
+            })
+            .build();
+
+        agent.train(&start, 30_000);
+
+        let convergence = agent.convergence(&start);
+        assert!(convergence.converged(), "{}", convergence);
+    }
+}
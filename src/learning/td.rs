@@ -0,0 +1,371 @@
+//! Temporal-difference learning of a value function.
+//!
+//! `TemporalDifference` estimates the score of a position for the player to move as the stones they are ahead, plus
+//! a learned correction for the stones still on the board. The correction is linear in features that encode how many
+//! stones each bowl holds, taken as the difference between the side of the player and the side of the opponent. So
+//! the estimate for the opponent is exactly the opposite, and an untrained agent evaluates like `Delta`.
+//!
+//! It learns with [TD(λ)](https://en.wikipedia.org/wiki/Temporal_difference_learning) by playing against itself. It
+//! plays greedily, but explores with a random play every now and then.
+//!
+//! ```
+//! use mancala::game::Position;
+//! use mancala::learning::TemporalDifference;
+//! use mancala::strategy::tree::Depth;
+//! use mancala::strategy::AlphaBeta;
+//!
+//! let mut agent = TemporalDifference::strategy().bowls(3).seeded(7).build();
+//! agent.train(&Position::new(3, 2), 10);
+//!
+//! let parameters = agent.to_string();
+//! let reloaded = TemporalDifference::from_parameters(&parameters).expect("parameters to be read back");
+//! let strategy = AlphaBeta::strategy().limited_to(Depth::Limit(3)).with_heuristic(reloaded).build();
+//! ```
+//!
+//! The parameters are written as a header with the number of bowls, followed by the weights in the format of
+//! `tuning::Weights`.
+//!
+//! ```text
+//! # td(λ) value function
+//! bowls 6
+//! 0.25
+//! -0.125
+//! ...
+//! ```
+
+use super::{bowls, load, read, sign, BadParameters};
+use crate::{
+    game::{Bowl, Pit, Player, Position, MAX_BOWLS},
+    strategy::{Heuristic, Strategy, Value},
+    tuning::Weights,
+};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The number of stones per bowl that have a feature of their own. Bowls with more stones share the last feature.
+const BUCKETS: usize = 16;
+
+/// Build TemporalDifference agents
+pub struct TemporalDifferenceBuilder {
+    bowls: usize,
+    learning_rate: f64,
+    trace_decay: f64,
+    exploration: f64,
+    seed: Option<u64>,
+}
+
+impl TemporalDifferenceBuilder {
+    /// Build an untrained TemporalDifference agent
+    ///
+    /// Panics if there are more than `MAX_BOWLS` bowls.
+    pub fn build(self) -> TemporalDifference {
+        assert!(self.bowls <= MAX_BOWLS, "at most {} bowls", MAX_BOWLS);
+        let weights = vec![0.0; self.bowls * BUCKETS];
+        TemporalDifference::with_weights(self, weights)
+    }
+
+    /// for positions with a certain number of bowls
+    pub fn bowls(mut self, bowls: usize) -> Self {
+        self.bowls = bowls;
+        self
+    }
+
+    /// with a certain learning rate
+    pub fn learning_rate(mut self, learning_rate: f64) -> Self {
+        self.learning_rate = learning_rate;
+        self
+    }
+
+    /// with a certain decay of the eligibility traces, the λ of TD(λ)
+    pub fn trace_decay(mut self, trace_decay: f64) -> Self {
+        self.trace_decay = trace_decay;
+        self
+    }
+
+    /// exploring with a random play with a certain probability while training
+    pub fn exploring(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    /// with a seed, so that training can be repeated
+    pub fn seeded(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+}
+
+/// Learn a value function by self-play, and play greedily by it.
+pub struct TemporalDifference {
+    bowls: usize,
+    weights: Vec<f64>,
+    learning_rate: f64,
+    trace_decay: f64,
+    exploration: f64,
+    episodes: usize,
+    rng: StdRng,
+}
+
+impl TemporalDifference {
+    /// Create a default TemporalDifferenceBuilder
+    ///
+    /// It is for positions with 6 bowls, with a learning rate of 0.005, a trace decay of 0.7 and an exploration of
+    /// 0.1.
+    pub fn strategy() -> TemporalDifferenceBuilder {
+        TemporalDifferenceBuilder {
+            bowls: 6,
+            learning_rate: 0.005,
+            trace_decay: 0.7,
+            exploration: 0.1,
+            seed: None,
+        }
+    }
+
+    fn with_weights(builder: TemporalDifferenceBuilder, weights: Vec<f64>) -> Self {
+        let rng = match builder.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        TemporalDifference {
+            bowls: builder.bowls,
+            weights,
+            learning_rate: builder.learning_rate,
+            trace_decay: builder.trace_decay,
+            exploration: builder.exploration,
+            episodes: 0,
+            rng,
+        }
+    }
+
+    /// Read an agent from its parameters, with the default settings for further training.
+    pub fn from_parameters(parameters: &str) -> Result<Self, BadParameters> {
+        let (header, weights) = read(parameters, &["bowls"])?;
+        let bowls = bowls(header[0])?;
+        if weights.values().len() != bowls * BUCKETS {
+            return Err(BadParameters::WrongNumberOfWeights);
+        }
+        Ok(TemporalDifference::with_weights(
+            TemporalDifference::strategy().bowls(bowls),
+            weights.values().to_vec(),
+        ))
+    }
+
+    /// Load an agent from a file with its parameters.
+    pub fn load<P>(path: P) -> Result<Self, BadParameters>
+    where
+        P: AsRef<Path>,
+    {
+        TemporalDifference::from_parameters(&load(path)?)
+    }
+
+    /// Save the parameters of this agent to a file.
+    pub fn save<P>(&self, path: P) -> io::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        fs::write(path, self.to_string())
+    }
+
+    /// The number of episodes this agent trained for.
+    pub fn episodes(&self) -> usize {
+        self.episodes
+    }
+
+    /// The estimated score of `position` for the player to move.
+    ///
+    /// Finished positions are valued by their score. Panics if `position` does not have the number of bowls of this
+    /// agent.
+    pub fn value(&self, position: &Position) -> f64 {
+        if let Some(score) = position.score() {
+            return score as f64;
+        }
+        let correction: f64 = self
+            .features(position)
+            .map(|(index, feature)| self.weights[index] * feature)
+            .sum();
+        position.delta() as f64 + correction
+    }
+
+    /// Train by playing `episodes` games against itself, starting from `start`.
+    ///
+    /// Returns the average score for `Red`.
+    pub fn train(&mut self, start: &Position, episodes: usize) -> f64 {
+        let total: f64 = (0..episodes).map(|_| self.episode(start)).sum();
+        if episodes == 0 {
+            0.0
+        } else {
+            total / episodes as f64
+        }
+    }
+
+    /// Play a single game against itself, updating the weights after every play.
+    ///
+    /// Values are taken from the perspective of `Red`, so that eligibility traces carry over when the player to move
+    /// changes.
+    fn episode(&mut self, start: &Position) -> f64 {
+        let mut position = *start;
+        let mut traces = vec![0.0; self.weights.len()];
+        while !position.finished() {
+            let side = sign(position.turn());
+            let value = side * self.value(&position);
+            for trace in traces.iter_mut() {
+                *trace *= self.trace_decay;
+            }
+            for (index, feature) in self.features(&position) {
+                traces[index] += side * feature;
+            }
+
+            let bowl = self
+                .explore(&position)
+                .expect("an unfinished game to have options");
+            position = position.play(bowl).expect("option to be playable");
+
+            let next = sign(position.turn()) * self.value(&position);
+            let error = next - value;
+            for (weight, trace) in self.weights.iter_mut().zip(&traces) {
+                *weight += self.learning_rate * error * trace;
+            }
+        }
+        self.episodes += 1;
+        position
+            .score_for(Player::Red)
+            .expect("finished game to have a score") as f64
+    }
+
+    fn explore(&mut self, position: &Position) -> Option<Bowl> {
+        if self.rng.gen::<f64>() < self.exploration {
+            position.options().choose(&mut self.rng).copied()
+        } else {
+            self.greedy(position)
+        }
+    }
+
+    /// The option with the best value for the player to move, the first one in case of a tie.
+    fn greedy(&self, position: &Position) -> Option<Bowl> {
+        let mut best: Option<(Bowl, f64)> = None;
+        for bowl in position.options() {
+            let after = position.play(bowl).expect("option to be playable");
+            let value = sign(position.turn()) * sign(after.turn()) * self.value(&after);
+            if best.map(|(_, best)| value > best).unwrap_or(true) {
+                best = Some((bowl, value));
+            }
+        }
+        best.map(|(bowl, _)| bowl)
+    }
+
+    /// The non-zero features of `position`, with the index of their weight.
+    fn features<'a>(&self, position: &'a Position) -> impl Iterator<Item = (usize, f64)> + 'a {
+        assert_eq!(
+            position.size(),
+            self.bowls,
+            "agent for {} bowls",
+            self.bowls
+        );
+        let size = position.size();
+        (0..size).flat_map(move |bowl| {
+            let own = bucket(bowl, position.stones(Pit::Bowl(bowl)) as usize);
+            let opponent = bucket(bowl, position.stones(Pit::Bowl(size + bowl)) as usize);
+            if own == opponent {
+                vec![]
+            } else {
+                vec![(own, 1.0), (opponent, -1.0)]
+            }
+        })
+    }
+}
+
+/// The index of the feature of `bowl` holding `stones`.
+fn bucket(bowl: Bowl, stones: usize) -> usize {
+    bowl * BUCKETS + stones.min(BUCKETS - 1)
+}
+
+impl Heuristic for TemporalDifference {
+    fn evaluate(&self, position: &Position) -> Value {
+        Value::estimate(self.value(position))
+    }
+}
+
+impl Strategy for TemporalDifference {
+    fn play(&mut self, position: &Position) -> Option<Bowl> {
+        self.greedy(position)
+    }
+}
+
+impl Display for TemporalDifference {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "# td(λ) value function")?;
+        writeln!(f, "bowls {}", self.bowls)?;
+        write!(f, "{}", Weights::new(self.weights.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::heuristic::delta;
+
+    fn agent() -> TemporalDifference {
+        TemporalDifference::strategy().bowls(3).seeded(7).build()
+    }
+
+    #[test]
+    fn untrained_agents_evaluate_like_delta() {
+        let position = Position::from((2, 5, [1, 0, 3, 2, 0, 1]));
+
+        assert_eq!(
+            agent().evaluate(&position).as_stones(),
+            delta().evaluate(&position).as_stones()
+        );
+    }
+
+    #[test]
+    fn values_are_antisymmetric() {
+        let mut agent = agent();
+        agent.train(&Position::new(3, 2), 20);
+        let position = Position::from((2, 5, [1, 0, 3, 2, 0, 1]));
+
+        assert!((agent.value(&position) + agent.value(&position.passed())).abs() < 1e-9);
+    }
+
+    #[test]
+    fn finished_positions_are_valued_by_their_score() {
+        let position = Position::from((5, 0, [0, 0, 2, 2]));
+        let agent = TemporalDifference::strategy().bowls(2).build();
+
+        assert_eq!(agent.value(&position), 1.0);
+    }
+
+    #[test]
+    fn training_changes_weights() {
+        let mut agent = agent();
+
+        agent.train(&Position::new(3, 2), 20);
+
+        assert_eq!(agent.episodes(), 20);
+        assert!(agent.weights.iter().any(|weight| *weight != 0.0));
+    }
+
+    #[test]
+    fn seeded_training_can_be_repeated() {
+        let (mut left, mut right) = (agent(), agent());
+
+        left.train(&Position::new(3, 2), 20);
+        right.train(&Position::new(3, 2), 20);
+
+        assert_eq!(left.weights, right.weights);
+    }
+
+    #[test]
+    fn plays_are_options() {
+        let position = Position::new(3, 2);
+
+        let bowl = agent().play(&position);
+
+        assert!(bowl
+            .map(|bowl| position.options().contains(&bowl))
+            .unwrap_or(false));
+    }
+}
@@ -0,0 +1,819 @@
+//! Learn to play from scratch, in the style of [AlphaZero](https://en.wikipedia.org/wiki/AlphaZero).
+//!
+//! `Zero` is a `MonteCarlo` tree search guided by a `Network`. The network supplies the priors, so every simulation
+//! selects a path through the tree with PUCT, which explores plays in proportion to their prior. The network also
+//! values the expanded leaf, instead of a playout. After all simulations the most visited play is picked.
+//!
+//! `SelfPlay` trains a network by letting `Zero` play `Bout`s against itself. Every generation
+//!
+//! 1. plays games, and adds a sample for every position to a replay buffer. A sample has the visits of the search as
+//!    policy, and the final score as value. The first plays of a game are sampled by their visits, for variety.
+//! 2. trains a candidate network on random batches from the replay buffer, starting from the best network so far.
+//! 3. gates the candidate with an evaluation match against the best network. The candidate becomes the best network
+//!    when it scores well enough.
+//! 4. saves the best network as a checkpoint.
+//!
+//! ```
+//! use mancala::game::GameBuilder;
+//! use mancala::learning::zero::{SelfPlay, Zero};
+//! use mancala::strategy::Strategy;
+//!
+//! let directory = std::env::temp_dir().join(format!("zero-doc-{}", std::process::id()));
+//! let mut pipeline = SelfPlay::training()
+//!     .on(GameBuilder::new().bowls(2).stones(2))
+//!     .hidden(&[8])
+//!     .simulations(8)
+//!     .games(2)
+//!     .steps(4)
+//!     .evaluation(1)
+//!     .checkpoints(&directory)
+//!     .seeded(7)
+//!     .build()
+//!     .expect("a playable game");
+//! pipeline.resume().expect("checkpoints to be readable");
+//! let generation = pipeline.generation().expect("checkpoint to be written");
+//! println!("{}", generation);
+//!
+//! let mut strategy = Zero::load(pipeline.checkpoint(1).expect("a checkpoint directory"))
+//!     .expect("a checkpoint to load");
+//! let bowl = strategy.play(&mancala::game::Position::new(2, 2));
+//! # std::fs::remove_dir_all(&directory).expect("directory to be removed");
+//! ```
+//!
+//! Checkpoints are networks in the format of `Network`, named after their generation. Next to them the replay buffer is
+//! saved, one sample per line: the value, the policy separated by `,` and the position in notation. Resuming continues
+//! from the latest checkpoint, with the replay buffer of that generation.
+
+use super::network::{total, Adam, Loss, Network, NetworkBuilder, Sample};
+use super::{sign, BadParameters};
+use crate::{
+    bout::Bout,
+    game::{BadSetup, Bowl, GameBuilder, Player, Position, Variant},
+    strategy::{MonteCarlo, SearchReport, Strategy},
+};
+use rand::{distributions::WeightedIndex, prelude::Distribution, rngs::StdRng, Rng, SeedableRng};
+use std::collections::VecDeque;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Build Zero strategy instances
+pub struct ZeroBuilder {
+    network: Network,
+    simulations: usize,
+    exploration: f64,
+    exploring: usize,
+    recording: bool,
+    seed: Option<u64>,
+}
+
+impl ZeroBuilder {
+    /// Build a Zero strategy
+    pub fn build(self) -> Zero {
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let search = MonteCarlo::strategy()
+            .simulations(self.simulations)
+            .exploration(self.exploration)
+            .valued()
+            .seeded(rng.gen())
+            .with_heuristic(self.network.clone())
+            .with_prior(self.network)
+            .build();
+        Zero {
+            search,
+            exploring: self.exploring,
+            record: if self.recording { Some(vec![]) } else { None },
+            rng,
+        }
+    }
+
+    /// with a certain number of simulations per play
+    pub fn simulations(mut self, simulations: usize) -> Self {
+        self.simulations = simulations;
+        self
+    }
+
+    /// with a certain exploration constant
+    pub fn exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    /// sampling the first `plays` plays by their visits, instead of picking the most visited
+    pub fn exploring(mut self, plays: usize) -> Self {
+        self.exploring = plays;
+        self
+    }
+
+    /// recording the visits of every search, see `Zero::take_record`
+    pub fn recording(mut self) -> Self {
+        self.recording = true;
+        self
+    }
+
+    /// with a random number generator seeded by `seed`, making the strategy deterministic
+    pub fn seeded(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+}
+
+/// Pick the most visited option of a tree search guided by a network.
+pub struct Zero {
+    search: MonteCarlo<Network, Network>,
+    exploring: usize,
+    record: Option<Vec<(Position, Vec<f64>)>>,
+    rng: StdRng,
+}
+
+impl Zero {
+    /// Create a default ZeroBuilder for `network`
+    ///
+    /// It runs 100 simulations per play with an exploration constant of 1.5, and always picks the most visited play.
+    pub fn strategy(network: Network) -> ZeroBuilder {
+        ZeroBuilder {
+            network,
+            simulations: 100,
+            exploration: 1.5,
+            exploring: 0,
+            recording: false,
+            seed: None,
+        }
+    }
+
+    /// Load a default Zero strategy from a checkpoint.
+    pub fn load<P>(path: P) -> Result<Self, BadParameters>
+    where
+        P: AsRef<Path>,
+    {
+        Network::load(path).map(|network| Zero::strategy(network).build())
+    }
+
+    /// The network that guides the search.
+    pub fn network(&self) -> &Network {
+        self.search.heuristic()
+    }
+
+    /// Take the recorded positions, with the fraction of visits of every bowl.
+    pub fn take_record(&mut self) -> Vec<(Position, Vec<f64>)> {
+        self.record.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+}
+
+impl Strategy for Zero {
+    fn play(&mut self, position: &Position) -> Option<Bowl> {
+        let start = Instant::now();
+        if position.finished() {
+            return self.search.play(position);
+        }
+        let tree = self.search.search(position);
+        let visits = tree.visits();
+        if let Some(record) = self.record.as_mut() {
+            record.push((*position, visits.clone()));
+        }
+        let bowl = if self.exploring > 0 {
+            self.exploring -= 1;
+            let distribution = WeightedIndex::new(&visits).expect("root to have visited children");
+            distribution.sample(&mut self.rng)
+        } else {
+            tree.most_visited_play().expect("root to have children")
+        };
+        self.search.report_on(&tree, Some(bowl), start);
+        Some(bowl)
+    }
+
+    fn report(&self) -> Option<&SearchReport> {
+        self.search.report()
+    }
+}
+
+/// Build SelfPlay pipelines
+pub struct SelfPlayBuilder {
+    setup: GameBuilder,
+    hidden: Vec<usize>,
+    simulations: usize,
+    exploration: f64,
+    exploring: usize,
+    games: usize,
+    capacity: usize,
+    batch_size: usize,
+    steps: usize,
+    learning_rate: f64,
+    evaluation: usize,
+    threshold: f64,
+    checkpoints: Option<PathBuf>,
+    seed: Option<u64>,
+}
+
+impl SelfPlayBuilder {
+    /// Build a SelfPlay pipeline, starting with an untrained network
+    ///
+    /// Fails when the setup does not create a playable game.
+    pub fn build(self) -> Result<SelfPlay, BadSetup> {
+        let bowls = self.setup.build()?.start().size();
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let best = NetworkBuilder::new()
+            .bowls(bowls)
+            .hidden(&self.hidden)
+            .seeded(rng.gen())
+            .build();
+        Ok(SelfPlay {
+            settings: self,
+            best,
+            buffer: VecDeque::new(),
+            generation: 0,
+            rng,
+        })
+    }
+
+    /// playing games created by a certain setup
+    pub fn on(mut self, setup: GameBuilder) -> Self {
+        self.setup = setup;
+        self
+    }
+
+    /// with hidden layers of certain sizes
+    pub fn hidden(mut self, hidden: &[usize]) -> Self {
+        self.hidden = hidden.to_vec();
+        self
+    }
+
+    /// with a certain number of simulations per play
+    pub fn simulations(mut self, simulations: usize) -> Self {
+        self.simulations = simulations;
+        self
+    }
+
+    /// with a certain exploration constant
+    pub fn exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    /// sampling the first `plays` plays of each side by their visits
+    pub fn exploring(mut self, plays: usize) -> Self {
+        self.exploring = plays;
+        self
+    }
+
+    /// playing a certain number of games per generation
+    pub fn games(mut self, games: usize) -> Self {
+        self.games = games;
+        self
+    }
+
+    /// keeping at most a certain number of samples in the replay buffer
+    pub fn buffer(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// training on batches of a certain size
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// training a certain number of steps per generation
+    pub fn steps(mut self, steps: usize) -> Self {
+        self.steps = steps;
+        self
+    }
+
+    /// training with a certain learning rate
+    pub fn learning_rate(mut self, learning_rate: f64) -> Self {
+        self.learning_rate = learning_rate;
+        self
+    }
+
+    /// gating with a certain number of pairs of games
+    pub fn evaluation(mut self, games: usize) -> Self {
+        self.evaluation = games;
+        self
+    }
+
+    /// accepting a candidate that scores at least `threshold`, between 0 and 1
+    pub fn gating(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// saving a checkpoint in `directory` every generation
+    pub fn checkpoints<P>(mut self, directory: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        self.checkpoints = Some(directory.as_ref().to_path_buf());
+        self
+    }
+
+    /// with a seed, so that training can be repeated
+    pub fn seeded(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+}
+
+/// Train a network by self-play.
+pub struct SelfPlay {
+    settings: SelfPlayBuilder,
+    best: Network,
+    buffer: VecDeque<Sample>,
+    generation: usize,
+    rng: StdRng,
+}
+
+/// The record of a generation of self-play.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Generation {
+    /// The number of the generation, counting from 1
+    pub generation: usize,
+    /// The number of games played
+    pub games: usize,
+    /// The number of samples in the replay buffer
+    pub samples: usize,
+    /// The loss of the candidate on its last batch
+    pub loss: Option<Loss>,
+    /// The score of the candidate against the best network, between 0 and 1
+    pub score: f64,
+    /// Whether the candidate became the best network
+    pub accepted: bool,
+}
+
+impl Display for Generation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "generation {:>4}: {} games, {} samples, ",
+            self.generation, self.games, self.samples
+        )?;
+        if let Some(loss) = self.loss {
+            write!(f, "{}, ", loss)?;
+        }
+        write!(
+            f,
+            "score {:.3} {}",
+            self.score,
+            if self.accepted {
+                "accepted"
+            } else {
+                "rejected"
+            }
+        )
+    }
+}
+
+impl SelfPlay {
+    /// Create a default SelfPlayBuilder
+    ///
+    /// It plays the default games of a `GameBuilder` with a network with a hidden layer of 32 units. Every generation
+    /// plays 20 games with 100 simulations per play, sampling the first 4 plays of each side, into a replay buffer of
+    /// 10000 samples. It trains 100 steps on batches of 64 with a learning rate of 0.001, and gates with 10 pairs of
+    /// games, accepting a candidate that scores at least 0.55. There are no checkpoints.
+    pub fn training() -> SelfPlayBuilder {
+        SelfPlayBuilder {
+            setup: GameBuilder::new(),
+            hidden: vec![32],
+            simulations: 100,
+            exploration: 1.5,
+            exploring: 4,
+            games: 20,
+            capacity: 10_000,
+            batch_size: 64,
+            steps: 100,
+            learning_rate: 0.001,
+            evaluation: 10,
+            threshold: 0.55,
+            checkpoints: None,
+            seed: None,
+        }
+    }
+
+    /// The best network so far.
+    pub fn best(&self) -> &Network {
+        &self.best
+    }
+
+    /// The number of generations so far.
+    pub fn generations(&self) -> usize {
+        self.generation
+    }
+
+    /// The path of the checkpoint of a generation, if there is a checkpoint directory.
+    pub fn checkpoint(&self, generation: usize) -> Option<PathBuf> {
+        self.settings
+            .checkpoints
+            .as_ref()
+            .map(|directory| directory.join(format!("generation-{:04}.txt", generation)))
+    }
+
+    /// The path of the replay buffer, if there is a checkpoint directory.
+    fn replay_buffer(&self) -> Option<PathBuf> {
+        self.settings
+            .checkpoints
+            .as_ref()
+            .map(|directory| directory.join("replay-buffer.txt"))
+    }
+
+    /// Continue from the latest checkpoint and its replay buffer, if there is one.
+    ///
+    /// Returns the generation of the checkpoint, 0 when there is none.
+    pub fn resume(&mut self) -> Result<usize, BadParameters> {
+        let directory = match &self.settings.checkpoints {
+            Some(directory) if directory.exists() => directory,
+            _ => return Ok(0),
+        };
+        let entries =
+            fs::read_dir(directory).map_err(|error| BadParameters::Unreadable(error.kind()))?;
+        let latest = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                name.strip_prefix("generation-")?
+                    .strip_suffix(".txt")?
+                    .parse::<usize>()
+                    .ok()
+            })
+            .max();
+        if let Some(generation) = latest {
+            let path = self.checkpoint(generation).expect("a checkpoint directory");
+            self.best = Network::load(path)?;
+            self.generation = generation;
+            let path = self.replay_buffer().expect("a checkpoint directory");
+            if path.exists() {
+                let variant = self.variant();
+                let buffer = fs::read_to_string(path)
+                    .map_err(|error| BadParameters::Unreadable(error.kind()))?;
+                self.buffer = buffer
+                    .lines()
+                    .enumerate()
+                    .map(|(index, line)| {
+                        read_sample(line, variant).ok_or(BadParameters::MalformedSample(index))
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+        }
+        Ok(self.generation)
+    }
+
+    /// Run a single generation, saving a checkpoint when there is a checkpoint directory.
+    pub fn generation(&mut self) -> io::Result<Generation> {
+        self.generation += 1;
+        for _ in 0..self.settings.games {
+            let samples = self.self_play();
+            self.buffer.extend(samples);
+        }
+        while self.buffer.len() > self.settings.capacity {
+            self.buffer.pop_front();
+        }
+
+        let mut candidate = self.best.clone();
+        let mut optimizer = Adam::new(self.settings.learning_rate);
+        let mut loss = None;
+        if !self.buffer.is_empty() {
+            for _ in 0..self.settings.steps {
+                let batch: Vec<Sample> = (0..self.settings.batch_size)
+                    .map(|_| self.buffer[self.rng.gen_range(0..self.buffer.len())].clone())
+                    .collect();
+                loss = Some(candidate.train(&batch, &mut optimizer));
+            }
+        }
+
+        let score = self.evaluate(&candidate);
+        let accepted = score >= self.settings.threshold;
+        if accepted {
+            self.best = candidate;
+        }
+
+        if let Some(path) = self.checkpoint(self.generation) {
+            fs::create_dir_all(path.parent().expect("a checkpoint directory"))?;
+            self.best.save(path)?;
+            let buffer: String = self.buffer.iter().map(write_sample).collect();
+            fs::write(
+                self.replay_buffer().expect("a checkpoint directory"),
+                buffer,
+            )?;
+        }
+        Ok(Generation {
+            generation: self.generation,
+            games: self.settings.games,
+            samples: self.buffer.len(),
+            loss,
+            score,
+            accepted,
+        })
+    }
+
+    fn variant(&self) -> Variant {
+        self.settings
+            .setup
+            .build()
+            .expect("a playable game")
+            .start()
+            .variant()
+    }
+
+    fn zero(&mut self, network: &Network) -> ZeroBuilder {
+        Zero::strategy(network.clone())
+            .simulations(self.settings.simulations)
+            .exploration(self.settings.exploration)
+            .exploring(self.settings.exploring)
+            .seeded(self.rng.gen())
+    }
+
+    /// Play a game of the best network against itself, returning a sample for every play.
+    fn self_play(&mut self) -> Vec<Sample> {
+        let best = self.best.clone();
+        let mut red = self.zero(&best).recording().build();
+        let mut blue = self.zero(&best).recording().build();
+        let game = self.settings.setup.build().expect("a playable game");
+        let game = Bout::new(&mut red, &mut blue, &|_| {})
+            .start(game)
+            .expect("zero to finish the game");
+        let outcome = game.outcome().expect("a finished game to have an outcome");
+        red.take_record()
+            .into_iter()
+            .chain(blue.take_record())
+            .map(|(position, policy)| {
+                let margin = sign(position.turn()) * outcome.margin() as f64;
+                Sample {
+                    position,
+                    value: margin / total(&position),
+                    policy,
+                }
+            })
+            .collect()
+    }
+
+    /// The score of `candidate` in pairs of games against the best network, 1 for a win and 0.5 for a draw.
+    fn evaluate(&mut self, candidate: &Network) -> f64 {
+        if self.settings.evaluation == 0 {
+            return 1.0;
+        }
+        let best = self.best.clone();
+        let mut score = 0.0;
+        for _ in 0..self.settings.evaluation {
+            for candidate_player in &[Player::Red, Player::Blue] {
+                let mut challenger = self.zero(candidate).build();
+                let mut defender = self.zero(&best).build();
+                let (red, blue): (&mut dyn Strategy, &mut dyn Strategy) = match candidate_player {
+                    Player::Red => (&mut challenger, &mut defender),
+                    Player::Blue => (&mut defender, &mut challenger),
+                };
+                let game = self.settings.setup.build().expect("a playable game");
+                let outcome = Bout::new(red, blue, &|_| {})
+                    .start(game)
+                    .expect("zero to finish the game")
+                    .outcome()
+                    .expect("a finished game to have an outcome");
+                score += match outcome.winner() {
+                    Some(winner) if winner == *candidate_player => 1.0,
+                    Some(_) => 0.0,
+                    None => 0.5,
+                };
+            }
+        }
+        score / (2 * self.settings.evaluation) as f64
+    }
+}
+
+/// A sample as a line of a replay buffer.
+fn write_sample(sample: &Sample) -> String {
+    let policy: Vec<String> = sample.policy.iter().map(f64::to_string).collect();
+    format!(
+        "{} {} {}\n",
+        sample.value,
+        policy.join(","),
+        sample.position.notation()
+    )
+}
+
+/// A sample from a line of a replay buffer, with its position played under `variant`.
+fn read_sample(line: &str, variant: Variant) -> Option<Sample> {
+    let mut fields = line.splitn(3, ' ');
+    let value = fields.next()?.parse().ok()?;
+    let policy = fields
+        .next()?
+        .split(',')
+        .map(|probability| probability.parse().ok())
+        .collect::<Option<_>>()?;
+    let position = fields.next()?.parse::<Position>().ok()?;
+    Some(Sample {
+        position: position.with_rules(variant),
+        value,
+        policy,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(bowls: usize) -> Network {
+        NetworkBuilder::new()
+            .bowls(bowls)
+            .hidden(&[8])
+            .seeded(7)
+            .build()
+    }
+
+    fn pipeline(directory: &Path) -> SelfPlay {
+        SelfPlay::training()
+            .on(GameBuilder::new().bowls(2).stones(2))
+            .hidden(&[8])
+            .simulations(8)
+            .games(2)
+            .batch_size(8)
+            .steps(4)
+            .evaluation(1)
+            .gating(0.0)
+            .checkpoints(directory)
+            .seeded(7)
+            .build()
+            .expect("a playable game")
+    }
+
+    fn directory(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("zero-{}-{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn winning_bowl_is_selected() {
+        let position = Position::from([1, 3, 0, 5, 0, 1]);
+        let mut strategy = Zero::strategy(network(3)).simulations(1000).build();
+
+        assert_eq!(strategy.play(&position), Some(0));
+    }
+
+    #[test]
+    fn records_hold_visit_distributions() {
+        let position = Position::new(3, 2);
+        let mut strategy = Zero::strategy(network(3))
+            .simulations(20)
+            .recording()
+            .seeded(7)
+            .build();
+
+        strategy.play(&position);
+        let record = strategy.take_record();
+
+        assert_eq!(record.len(), 1);
+        assert_eq!(record[0].0, position);
+        assert!((record[0].1.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(strategy.take_record().is_empty());
+    }
+
+    #[test]
+    fn single_simulations_visit_the_most_promising_play() {
+        let position = Position::new(3, 2);
+        let network = network(3);
+        let priors = network.predict(&position).priors;
+        let favourite = (0..priors.len())
+            .max_by(|left, right| priors[*left].partial_cmp(&priors[*right]).unwrap())
+            .expect("a bowl");
+        let mut strategy = Zero::strategy(network)
+            .simulations(1)
+            .exploring(1)
+            .recording()
+            .seeded(7)
+            .build();
+
+        let bowl = strategy.play(&position);
+
+        assert_eq!(bowl, Some(favourite));
+        assert_eq!(strategy.take_record()[0].1[favourite], 1.0);
+        assert!(strategy.play(&position).is_some());
+    }
+
+    #[test]
+    fn report_follows_most_visited_plays() {
+        let position = Position::new(3, 2);
+        let mut strategy = Zero::strategy(network(3)).simulations(50).build();
+
+        let bowl = strategy.play(&position);
+        let report = strategy.report().expect("a report");
+
+        assert_eq!(report.principal_variation.first().copied(), bowl);
+        assert!(report.value.is_some());
+    }
+
+    #[test]
+    fn generations_fill_the_buffer_and_save_checkpoints() {
+        let directory = directory("generations");
+        let mut pipeline = pipeline(&directory);
+
+        let generation = pipeline.generation().expect("checkpoint to be written");
+        let checkpoint = pipeline.checkpoint(1).expect("a checkpoint directory");
+        let loaded = Network::load(&checkpoint);
+        fs::remove_dir_all(&directory).expect("directory to be removed");
+
+        assert_eq!(generation.generation, 1);
+        assert!(generation.samples > 0);
+        assert!(generation.loss.is_some());
+        assert!(generation.accepted);
+        assert_eq!(loaded.as_ref(), Ok(pipeline.best()));
+    }
+
+    #[test]
+    fn resuming_continues_from_the_latest_checkpoint() {
+        let directory = directory("resume");
+        let mut first = pipeline(&directory);
+        first.generation().expect("checkpoint to be written");
+        first.generation().expect("checkpoint to be written");
+
+        let mut second = pipeline(&directory);
+        let resumed = second.resume();
+        let buffer = second.buffer.clone();
+        let generation = second.generation();
+        fs::remove_dir_all(&directory).expect("directory to be removed");
+
+        assert_eq!(resumed, Ok(2));
+        assert!(!buffer.is_empty());
+        assert_eq!(buffer, first.buffer);
+        assert_eq!(
+            generation.map(|generation| generation.generation).ok(),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn malformed_replay_buffers_are_rejected() {
+        let directory = directory("buffer");
+        let mut first = pipeline(&directory);
+        first.generation().expect("checkpoint to be written");
+        let path = first.replay_buffer().expect("a checkpoint directory");
+        fs::write(&path, "0.5 1,0 2,2/2,2 0 0 r\n0.5 x 2,2/2,2 0 0 r\n")
+            .expect("replay buffer to be written");
+
+        let resumed = pipeline(&directory).resume();
+        fs::remove_dir_all(&directory).expect("directory to be removed");
+
+        assert_eq!(resumed, Err(BadParameters::MalformedSample(1)));
+    }
+
+    #[test]
+    fn checkpoints_play_as_strategies() {
+        let directory = directory("strategy");
+        let mut pipeline = pipeline(&directory);
+        pipeline.generation().expect("checkpoint to be written");
+
+        let strategy = Zero::load(pipeline.checkpoint(1).expect("a checkpoint directory"));
+        fs::remove_dir_all(&directory).expect("directory to be removed");
+
+        let position = Position::new(2, 2);
+        let bowl = strategy.expect("a checkpoint to load").play(&position);
+        assert!(bowl
+            .map(|bowl| position.options().contains(&bowl))
+            .unwrap_or(false));
+    }
+
+    #[test]
+    fn generations_run_with_a_single_simulation() {
+        let mut pipeline = SelfPlay::training()
+            .on(GameBuilder::new().bowls(2).stones(2))
+            .hidden(&[8])
+            .simulations(1)
+            .games(1)
+            .steps(1)
+            .evaluation(1)
+            .seeded(7)
+            .build()
+            .expect("a playable game");
+
+        let generation = pipeline.generation().expect("no checkpoint to be written");
+
+        assert!(generation.samples > 0);
+    }
+
+    #[test]
+    fn rejected_candidates_keep_the_best_network() {
+        let directory = directory("gating");
+        let mut pipeline = SelfPlay::training()
+            .on(GameBuilder::new().bowls(2).stones(2))
+            .hidden(&[8])
+            .simulations(8)
+            .games(1)
+            .steps(2)
+            .evaluation(1)
+            .gating(1.1)
+            .checkpoints(&directory)
+            .seeded(7)
+            .build()
+            .expect("a playable game");
+        let before = pipeline.best().clone();
+
+        let generation = pipeline.generation().expect("checkpoint to be written");
+        fs::remove_dir_all(&directory).expect("directory to be removed");
+
+        assert!(!generation.accepted);
+        assert_eq!(pipeline.best(), &before);
+    }
+}
@@ -4,4 +4,6 @@
 
 pub mod bout;
 pub mod game;
+pub mod learning;
 pub mod strategy;
+pub mod tuning;
@@ -0,0 +1,160 @@
+//! Analyse every option of a position.
+//!
+//! Where a `Strategy` settles on a single play, an analysis determines the value of every option. The options are
+//! sorted from best to worst, and the best lines of play are included.
+//!
+//! ```
+//! use mancala::game::Position;
+//! use mancala::strategy::{AlphaBeta, Analyse};
+//! use mancala::strategy::tree::Depth;
+//!
+//! let mut strategy = AlphaBeta::strategy().build();
+//! let analysis = strategy.analyse(&Position::new(6, 4), &Depth::Limit(4), 2);
+//!
+//! println!("{}", analysis);
+//! ```
+
+use crate::{
+    game::{Bowl, Position},
+    strategy::{
+        heuristic::Value,
+        tree::{Depth, DepthLimitedSearch},
+    },
+};
+use std::fmt::{self, Display, Formatter};
+
+/// Determine the value of every option.
+pub trait Analyse {
+    /// Analyse all options of `position`, searching `search_depth` levels.
+    ///
+    /// The best `lines` options come with their principal variation.
+    fn analyse(&mut self, position: &Position, search_depth: &Depth, lines: usize) -> Analysis;
+}
+
+/// The value of a single option.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Line {
+    /// The bowl to play.
+    pub bowl: Bowl,
+    /// The value of playing `bowl`, for the player to move.
+    pub value: Value,
+    /// The expected line of play, starting with `bowl`.
+    ///
+    /// Only the best lines of an analysis look further than `bowl`.
+    pub principal_variation: Vec<Bowl>,
+}
+
+/// The values of all options of a position, best first.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Analysis {
+    lines: Vec<Line>,
+}
+
+impl Analysis {
+    /// Create an analysis from `lines`, in any order, keeping the principal variation of the best `best` lines.
+    pub fn new(mut lines: Vec<Line>, best: usize) -> Self {
+        lines.sort_by(|left, right| {
+            right
+                .value
+                .cmp(&left.value)
+                .then(left.bowl.cmp(&right.bowl))
+        });
+        for line in lines.iter_mut().skip(best) {
+            line.principal_variation.truncate(1);
+        }
+        Analysis { lines }
+    }
+
+    /// All lines, best first.
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    /// The best line, if there are any options.
+    pub fn best(&self) -> Option<&Line> {
+        self.lines.first()
+    }
+}
+
+impl Display for Analysis {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        let lines: Vec<String> = self
+            .lines
+            .iter()
+            .map(|line| format!("bowl {}: {}", line.bowl, line.value))
+            .collect();
+        write!(formatter, "{}", lines.join(", "))
+    }
+}
+
+/// Analyse `position` by searching every option with `searcher`.
+pub(crate) fn analyse_with<S>(
+    searcher: &mut S,
+    position: &Position,
+    search_depth: &Depth,
+    best: usize,
+) -> Analysis
+where
+    S: DepthLimitedSearch<Position, (Option<Bowl>, Value)>,
+{
+    let lines = position
+        .options()
+        .into_iter()
+        .map(|bowl| {
+            let candidate = position.play(bowl).expect("option to be playable");
+            let (_, value) = searcher.search(&candidate, &search_depth.decrement());
+            let value = if candidate.turn() == position.turn() {
+                value
+            } else {
+                value.opposite()
+            };
+            let mut principal_variation = vec![bowl];
+            if let Some(report) = searcher.search_report() {
+                principal_variation.extend(&report.principal_variation);
+            }
+            Line {
+                bowl,
+                value,
+                principal_variation,
+            }
+        })
+        .collect();
+    Analysis::new(lines, best)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(bowl: Bowl, score: i16) -> Line {
+        Line {
+            bowl,
+            value: Value::Actual(score),
+            principal_variation: vec![bowl, 0],
+        }
+    }
+
+    #[test]
+    fn lines_are_sorted_best_first() {
+        let analysis = Analysis::new(vec![line(0, 1), line(1, 4), line(2, 1)], 3);
+
+        let bowls: Vec<Bowl> = analysis.lines().iter().map(|line| line.bowl).collect();
+
+        assert_eq!(bowls, vec![1, 0, 2]);
+    }
+
+    #[test]
+    fn only_best_lines_keep_their_variation() {
+        let analysis = Analysis::new(vec![line(0, 1), line(1, 4)], 1);
+
+        assert_eq!(analysis.lines()[0].principal_variation, vec![1, 0]);
+        assert_eq!(analysis.lines()[1].principal_variation, vec![0]);
+    }
+
+    #[test]
+    fn analysis_is_displayed_as_values_per_bowl() {
+        let analysis = Analysis::new(vec![line(5, 2), line(3, 4), line(0, -1)], 0);
+
+        assert_eq!(analysis.to_string(), "bowl 3: +4, bowl 5: +2, bowl 0: -1");
+    }
+}
@@ -0,0 +1,293 @@
+//! Positional features of a position.
+//!
+//! Each feature compares the player to move with the opponent. A positive value means the feature favours the player
+//! to move.
+//!
+//! Features that look ahead a single play only consider plays that are allowed. Under some rules, a finished position
+//! still has options that can not be played.
+
+use super::{Heuristic, Value};
+use crate::game::{Bowl, Pit, Position, Stones};
+
+/// The stones on the side of the player, minus the stones on the side of the opponent.
+pub struct StonesOnSide {}
+
+/// create a stones on side heuristic
+pub fn stones_on_side() -> StonesOnSide {
+    StonesOnSide {}
+}
+
+impl Heuristic for StonesOnSide {
+    fn evaluate(&self, position: &Position) -> Value {
+        balance(position, |position| {
+            (0..position.size())
+                .map(|bowl| position.stones(Pit::Bowl(bowl)) as i32)
+                .sum()
+        })
+    }
+}
+
+/// The number of options of the player, minus the number of options of the opponent.
+pub struct Mobility {}
+
+/// create a mobility heuristic
+pub fn mobility() -> Mobility {
+    Mobility {}
+}
+
+impl Heuristic for Mobility {
+    fn evaluate(&self, position: &Position) -> Value {
+        balance(position, |position| position.options().len() as i32)
+    }
+}
+
+/// The number of plays that earn the player an extra turn, minus those of the opponent.
+pub struct ExtraTurns {}
+
+/// create an extra turns heuristic
+pub fn extra_turns() -> ExtraTurns {
+    ExtraTurns {}
+}
+
+impl Heuristic for ExtraTurns {
+    fn evaluate(&self, position: &Position) -> Value {
+        balance(position, |position| {
+            position
+                .options()
+                .into_iter()
+                .filter_map(|bowl| position.play(bowl).ok())
+                .filter(|after| after.turn() == position.turn() && !after.finished())
+                .count() as i32
+        })
+    }
+}
+
+/// The most stones the player can capture in a single play, minus the most stones the opponent can capture.
+pub struct CaptureThreats {}
+
+/// create a capture threats heuristic
+pub fn capture_threats() -> CaptureThreats {
+    CaptureThreats {}
+}
+
+impl Heuristic for CaptureThreats {
+    fn evaluate(&self, position: &Position) -> Value {
+        balance(position, |position| {
+            position
+                .options()
+                .into_iter()
+                .filter_map(|bowl| {
+                    let after = position.play(bowl).ok()?;
+                    Some(captured(position, bowl, &after) as i32)
+                })
+                .max()
+                .unwrap_or(0)
+        })
+    }
+}
+
+/// The stones of the opponent that are about to be captured, minus the stones of the player that are.
+///
+/// A stone is about to be captured when it is in a bowl that one of the plays of the other side empties.
+pub struct AboutToBeCaptured {}
+
+/// create an about to be captured heuristic
+pub fn about_to_be_captured() -> AboutToBeCaptured {
+    AboutToBeCaptured {}
+}
+
+impl Heuristic for AboutToBeCaptured {
+    fn evaluate(&self, position: &Position) -> Value {
+        balance(position, exposed)
+    }
+}
+
+/// The number of plays that keep all stones on the side of the player, minus those of the opponent.
+///
+/// These plays do not give stones to the opponent. The side with more of them can wait longer, and is less likely to
+/// run out of plays first.
+pub struct Tempo {}
+
+/// create a tempo heuristic
+pub fn tempo() -> Tempo {
+    Tempo {}
+}
+
+impl Heuristic for Tempo {
+    fn evaluate(&self, position: &Position) -> Value {
+        balance(position, |position| {
+            position
+                .options()
+                .into_iter()
+                .filter(|bowl| {
+                    sown(position, *bowl).all(|pit| match pit {
+                        Pit::Bowl(bowl) => bowl < position.size(),
+                        Pit::Store => true,
+                        Pit::OpponentStore => false,
+                    })
+                })
+                .count() as i32
+        })
+    }
+}
+
+/// The difference between `feature` for the player to move and `feature` for the opponent.
+fn balance<F>(position: &Position, feature: F) -> Value
+where
+    F: Fn(&Position) -> i32,
+{
+    let difference = feature(position) - feature(&position.passed());
+    Value::estimate(difference as f64)
+}
+
+/// The pits that playing `bowl` sows stones in, in order.
+fn sown(position: &Position, bowl: Bowl) -> impl Iterator<Item = Pit> {
+    let variant = position.variant();
+    let size = position.size();
+    let stones = position.stones(Pit::Bowl(bowl));
+    (0..stones).scan(Pit::Bowl(bowl), move |pit, _| {
+        *pit = variant.ruleset().next(size, bowl, *pit);
+        Some(*pit)
+    })
+}
+
+/// The number of stones captured by playing `bowl` in `position`, resulting in `after`.
+///
+/// The stones that the player gained in their store, minus the stones sown in it.
+pub(crate) fn captured(position: &Position, bowl: Bowl, after: &Position) -> Stones {
+    let player = position.turn();
+    let sown = sown(position, bowl)
+        .filter(|pit| *pit == Pit::Store)
+        .count() as Stones;
+    (after.store(player) - position.store(player)).saturating_sub(sown)
+}
+
+/// The stones of the opponent that the player to move can capture.
+///
+/// Sowing only adds stones, so a bowl of the opponent that is emptied by a play is captured.
+fn exposed(position: &Position) -> i32 {
+    let size = position.size();
+    let mut targets = vec![false; size];
+    for after in position
+        .options()
+        .into_iter()
+        .filter_map(|bowl| position.play(bowl).ok())
+    {
+        for (target, exposed) in targets.iter_mut().enumerate() {
+            let index = size + target;
+            let before = position.stones(Pit::Bowl(index));
+            if before > 0 && stones_after(position, &after, index) == 0 {
+                *exposed = true;
+            }
+        }
+    }
+    targets
+        .iter()
+        .enumerate()
+        .filter(|(_, exposed)| **exposed)
+        .map(|(target, _)| position.stones(Pit::Bowl(size + target)) as i32)
+        .sum()
+}
+
+/// The stones in `bowl`, relative to the player to move in `position`, after the play that lead to `after`.
+fn stones_after(position: &Position, after: &Position, bowl: Bowl) -> Stones {
+    if after.turn() == position.turn() {
+        after.stones(Pit::Bowl(bowl))
+    } else {
+        after.stones(Pit::Bowl((bowl + position.size()) % (2 * position.size())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Oware;
+
+    #[test]
+    fn stones_on_side_compares_sides() {
+        let position = Position::from([1, 2, 3, 0, 1, 1]);
+
+        assert_eq!(stones_on_side().evaluate(&position), Value::estimate(4.0));
+    }
+
+    #[test]
+    fn mobility_compares_options() {
+        let position = Position::from([1, 2, 3, 0, 0, 1]);
+
+        assert_eq!(mobility().evaluate(&position), Value::estimate(2.0));
+    }
+
+    #[test]
+    fn extra_turns_compares_plays_into_store() {
+        let position = Position::from([0, 0, 1, 1, 0, 2, 0, 0]);
+
+        assert_eq!(extra_turns().evaluate(&position), Value::estimate(1.0));
+    }
+
+    #[test]
+    fn capture_threats_compares_biggest_captures() {
+        let position = Position::from([1, 0, 0, 3, 0, 0, 0, 0, 2, 0]);
+
+        assert_eq!(capture_threats().evaluate(&position), Value::estimate(2.0));
+    }
+
+    #[test]
+    fn about_to_be_captured_counts_exposed_stones() {
+        let position = Position::from([3, 0, 0, 0, 1, 0]);
+
+        assert_eq!(
+            about_to_be_captured().evaluate(&position),
+            Value::estimate(-3.0)
+        );
+    }
+
+    #[test]
+    fn tempo_compares_plays_that_stay_on_side() {
+        let position = Position::from([1, 1, 4, 4, 4, 4]);
+
+        assert_eq!(tempo().evaluate(&position), Value::estimate(2.0));
+    }
+
+    #[test]
+    fn captured_stones_exclude_sown_stones() {
+        let position = Position::from([1, 0, 0, 3, 0, 0, 0, 0, 2, 0]);
+        let after = position.play(0).expect("a playable bowl");
+
+        assert_eq!(captured(&position, 0, &after), 2);
+    }
+
+    #[test]
+    fn features_are_antisymmetric() {
+        let position = Position::from([2, 0, 3, 1, 1, 0, 4, 2]);
+        let features: Vec<Box<dyn Heuristic>> = vec![
+            Box::new(stones_on_side()),
+            Box::new(mobility()),
+            Box::new(extra_turns()),
+            Box::new(capture_threats()),
+            Box::new(about_to_be_captured()),
+            Box::new(tempo()),
+        ];
+
+        for feature in features {
+            assert_eq!(
+                feature.evaluate(&position),
+                feature.evaluate(&position.passed()).opposite()
+            );
+        }
+    }
+
+    #[test]
+    fn finished_oware_positions_can_be_evaluated() {
+        let position = Position::from((5, 0, [0, 0, 1, 1, 1, 1])).with_rules(Oware::new());
+        let features: Vec<Box<dyn Heuristic>> = vec![
+            Box::new(extra_turns()),
+            Box::new(capture_threats()),
+            Box::new(about_to_be_captured()),
+        ];
+
+        assert!(position.finished());
+        for feature in features {
+            assert_eq!(feature.evaluate(&position), Value::estimate(0.0));
+        }
+    }
+}
@@ -0,0 +1,328 @@
+//! Estimate a value for a position.
+//!
+//! A [heuristic](https://en.wikipedia.org/wiki/Heuristic) is
+//!
+//! > any approach to problem solving or self-discovery that employs a practical method that is not guaranteed to be optimal, perfect, or rational, but is nevertheless sufficient for reaching an immediate, short-term goal or approximation.
+//!
+//! Besides `Delta`, there are heuristics for positional features: `StonesOnSide`, `Mobility`, `ExtraTurns`,
+//! `CaptureThreats`, `AboutToBeCaptured` and `Tempo`. `Weighted` combines heuristics into a stronger one.
+//!
+//! Where a heuristic values a position, a `Prior` estimates how promising each of its plays is.
+//!
+//! ```
+//! use mancala::strategy::AlphaBeta;
+//! use mancala::strategy::heuristic::{delta, extra_turns, mobility, Weighted};
+//! use mancala::strategy::tree::Depth;
+//!
+//! let heuristic = Weighted::new()
+//!     .with(1.0, delta())
+//!     .with(0.25, extra_turns())
+//!     .with(0.125, mobility());
+//! let strategy = AlphaBeta::strategy().limited_to(Depth::Limit(5)).with_heuristic(heuristic).build();
+//! ```
+
+mod features;
+mod weighted;
+
+pub(crate) use self::features::captured;
+pub use self::features::{
+    about_to_be_captured, capture_threats, extra_turns, mobility, stones_on_side, tempo,
+    AboutToBeCaptured, CaptureThreats, ExtraTurns, Mobility, StonesOnSide, Tempo,
+};
+pub use self::weighted::Weighted;
+
+use crate::game::{Position, Score};
+use std::cmp::{Ord, Ordering, PartialOrd};
+use std::fmt::{self, Display, Formatter};
+
+/// A way to evaluate a position without full knowledge of the game tree
+pub trait Heuristic {
+    /// Return the heuristic value for this position
+    fn evaluate(&self, position: &Position) -> Value;
+}
+
+impl<F> Heuristic for F
+where
+    F: Fn(&Position) -> Value,
+{
+    fn evaluate(&self, position: &Position) -> Value {
+        self(position)
+    }
+}
+
+/// A way to estimate how promising the plays of a position are
+pub trait Prior {
+    /// Return a probability for every bowl of the player to move
+    ///
+    /// The probabilities of the options sum to 1, other bowls have probability 0.
+    fn priors(&self, position: &Position) -> Vec<f64>;
+}
+
+impl<F> Prior for F
+where
+    F: Fn(&Position) -> Vec<f64>,
+{
+    fn priors(&self, position: &Position) -> Vec<f64> {
+        self(position)
+    }
+}
+
+/// A prior that finds every option equally promising.
+pub struct Uniform {}
+
+/// create a uniform prior
+pub fn uniform() -> Uniform {
+    Uniform {}
+}
+
+impl Prior for Uniform {
+    fn priors(&self, position: &Position) -> Vec<f64> {
+        let options = position.options();
+        let mut priors = vec![0.0; position.size()];
+        for bowl in &options {
+            priors[*bowl] = 1.0 / options.len() as f64;
+        }
+        priors
+    }
+}
+
+/// Positions can have a value `v`
+///
+/// It ranges from -∞ and ∞. Exact scores of finished games are `Actual`, finer grained estimates are `Estimate`.
+/// Values are ordered by the number of stones they represent. When an exact value and an estimate represent the same
+/// number of stones, the estimate is the lesser, so `Estimate(1000) < Actual(1)`. Values are only equal when they are
+/// the same variant, so an exact value never equals an estimate.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Value {
+    /// Represent the lowest possible value for a position
+    NegativeInfinity,
+    /// The exact value of a position, in stones
+    Actual(Score),
+    /// An estimate of the value of a position, in `1 / Value::RESOLUTION` of a stone
+    Estimate(i32),
+    /// Represent the highest possible value for a position
+    PositiveInfinity,
+}
+
+impl Value {
+    /// The number of steps an `Estimate` makes per stone.
+    pub const RESOLUTION: i32 = 1000;
+
+    /// An estimate of a certain number of stones, rounded to the resolution.
+    ///
+    /// Estimates that are out of range saturate, `NaN` is treated as 0.
+    pub fn estimate(stones: f64) -> Self {
+        // Casting a float to an integer saturates, and turns NaN into 0.
+        Value::Estimate((stones * Value::RESOLUTION as f64).round() as i32)
+    }
+
+    /// Determine if this is the exact score of a position
+    pub fn is_exact(&self) -> bool {
+        matches!(self, Value::Actual(_))
+    }
+
+    /// The number of stones this value represents, if it is finite
+    pub fn as_stones(&self) -> Option<f64> {
+        self.scaled()
+            .map(|scaled| scaled as f64 / Value::RESOLUTION as f64)
+    }
+
+    /// Determine the opposite of a Value
+    /// -∞ → ∞
+    ///  s → -s
+    ///  ∞ → -∞
+    ///
+    /// This reverses the order of values that represent a different number of stones.
+    pub fn opposite(self) -> Self {
+        match self {
+            Value::NegativeInfinity => Value::PositiveInfinity,
+            Value::Actual(score) => Value::Actual(-score),
+            Value::Estimate(estimate) => Value::Estimate(estimate.saturating_neg()),
+            Value::PositiveInfinity => Value::NegativeInfinity,
+        }
+    }
+
+    fn scaled(&self) -> Option<i64> {
+        match self {
+            Value::Actual(score) => Some(*score as i64 * Value::RESOLUTION as i64),
+            Value::Estimate(estimate) => Some(*estimate as i64),
+            _ => None,
+        }
+    }
+}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Value {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (*self, *other) {
+            (Value::NegativeInfinity, Value::NegativeInfinity) => Ordering::Equal,
+            (Value::NegativeInfinity, _) => Ordering::Less,
+            (_, Value::NegativeInfinity) => Ordering::Greater,
+            (Value::PositiveInfinity, Value::PositiveInfinity) => Ordering::Equal,
+            (Value::PositiveInfinity, _) => Ordering::Greater,
+            (_, Value::PositiveInfinity) => Ordering::Less,
+            (left, right) => left
+                .scaled()
+                .cmp(&right.scaled())
+                .then(left.is_exact().cmp(&right.is_exact())),
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::NegativeInfinity => write!(formatter, "-∞"),
+            Value::Actual(score) => write!(formatter, "{:+}", score),
+            Value::Estimate(estimate) => write!(
+                formatter,
+                "{:+.3}",
+                *estimate as f64 / Value::RESOLUTION as f64
+            ),
+            Value::PositiveInfinity => write!(formatter, "+∞"),
+        }
+    }
+}
+
+/// A simple heuristic that looks at the difference between the captured stones.
+pub struct Delta {}
+
+/// create a delta heuristic
+pub fn delta() -> Delta {
+    Delta {}
+}
+
+impl Heuristic for Delta {
+    fn evaluate(&self, position: &Position) -> Value {
+        match position.score() {
+            Some(score) => Value::Actual(score),
+            None => Value::estimate(position.delta() as f64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_compare_correctly() {
+        assert!(Value::NegativeInfinity < Value::Actual(0));
+        assert!(Value::NegativeInfinity < Value::PositiveInfinity);
+        assert!(Value::Actual(0) > Value::NegativeInfinity);
+        assert!(Value::Actual(0) < Value::PositiveInfinity);
+        assert!(Value::PositiveInfinity > Value::NegativeInfinity);
+        assert!(Value::PositiveInfinity > Value::Actual(0));
+    }
+
+    #[test]
+    fn values_are_displayed_with_sign() {
+        assert_eq!(Value::NegativeInfinity.to_string(), "-∞");
+        assert_eq!(Value::Actual(-3).to_string(), "-3");
+        assert_eq!(Value::Actual(0).to_string(), "+0");
+        assert_eq!(Value::Actual(4).to_string(), "+4");
+        assert_eq!(Value::PositiveInfinity.to_string(), "+∞");
+    }
+
+    #[test]
+    fn estimates_compare_with_actual_values() {
+        assert!(Value::Estimate(1500) > Value::Actual(1));
+        assert!(Value::Estimate(1500) < Value::Actual(2));
+        assert!(Value::Estimate(-1) < Value::Actual(0));
+        assert!(Value::Estimate(i32::MAX) < Value::PositiveInfinity);
+        assert!(Value::Estimate(i32::MIN) > Value::NegativeInfinity);
+        assert!(Value::Estimate(2000) < Value::Actual(2));
+        assert!(Value::Estimate(-2000) < Value::Actual(-2));
+    }
+
+    #[test]
+    fn small_differences_are_kept() {
+        assert!(Value::estimate(0.251) > Value::estimate(0.25));
+        assert_eq!(Value::estimate(0.25), Value::Estimate(250));
+    }
+
+    #[test]
+    fn exact_values_are_distinguishable() {
+        assert!(Value::Actual(2).is_exact());
+        assert!(!Value::Estimate(2000).is_exact());
+        assert!(!Value::PositiveInfinity.is_exact());
+    }
+
+    #[test]
+    fn exact_values_do_not_equal_estimates() {
+        assert_ne!(Value::Actual(2), Value::Estimate(2000));
+        assert_eq!(Value::Actual(2), Value::Actual(2));
+        assert_eq!(Value::Estimate(2000), Value::estimate(2.0));
+    }
+
+    #[test]
+    fn order_agrees_with_equality() {
+        let values = vec![
+            Value::NegativeInfinity,
+            Value::Actual(-2),
+            Value::Estimate(-2000),
+            Value::Actual(0),
+            Value::Estimate(0),
+            Value::Estimate(1),
+            Value::Actual(2),
+            Value::Estimate(2000),
+            Value::PositiveInfinity,
+        ];
+
+        for left in &values {
+            for right in &values {
+                assert_eq!(left == right, left.cmp(right) == Ordering::Equal);
+            }
+        }
+    }
+
+    #[test]
+    fn delta_estimates_unfinished_positions() {
+        assert_eq!(
+            delta().evaluate(&Position::from((3, 1, [1, 0, 1, 0]))),
+            Value::estimate(2.0)
+        );
+        assert_eq!(
+            delta().evaluate(&Position::from((3, 1, [0, 0, 1, 0]))),
+            Value::Actual(1)
+        );
+    }
+
+    #[test]
+    fn opposite_reverses_order() {
+        let values = vec![
+            Value::NegativeInfinity,
+            Value::Actual(-2),
+            Value::Estimate(-1500),
+            Value::Actual(0),
+            Value::Estimate(1),
+            Value::Actual(3),
+            Value::PositiveInfinity,
+        ];
+
+        for left in &values {
+            for right in &values {
+                assert_eq!(left.cmp(right), right.opposite().cmp(&left.opposite()));
+            }
+        }
+    }
+
+    #[test]
+    fn uniform_priors_spread_over_options() {
+        let position = Position::from([1, 0, 2, 0, 1, 1]);
+
+        assert_eq!(uniform().priors(&position), vec![0.5, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn estimates_are_displayed_as_fractions() {
+        assert_eq!(Value::Estimate(250).to_string(), "+0.250");
+        assert_eq!(Value::Estimate(-1500).to_string(), "-1.500");
+        assert_eq!(Value::estimate(0.5).as_stones(), Some(0.5));
+    }
+}
@@ -0,0 +1,111 @@
+//! Combine heuristics linearly.
+
+use super::{Heuristic, Value};
+use crate::game::Position;
+
+/// A weighted sum of heuristics.
+///
+/// ```
+/// use mancala::game::Position;
+/// use mancala::strategy::heuristic::{delta, mobility, Heuristic, Value, Weighted};
+///
+/// let heuristic = Weighted::new().with(4.0, delta()).with(0.5, mobility());
+///
+/// assert_eq!(heuristic.evaluate(&Position::new(6, 4)), Value::Estimate(0));
+/// ```
+///
+/// The sum is an estimate. When one of the heuristics evaluates to an infinite value, the sum is infinite as well.
+#[derive(Default)]
+pub struct Weighted {
+    terms: Vec<(f64, Box<dyn Heuristic>)>,
+}
+
+impl Weighted {
+    /// Create a weighted sum without any heuristics, which evaluates to 0.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// with `heuristic` added with a certain weight
+    pub fn with<H>(mut self, weight: f64, heuristic: H) -> Self
+    where
+        H: Heuristic + 'static,
+    {
+        self.terms.push((weight, Box::new(heuristic)));
+        self
+    }
+
+    /// The weights of the heuristics, in the order they were added.
+    pub fn weights(&self) -> Vec<f64> {
+        self.terms.iter().map(|(weight, _)| *weight).collect()
+    }
+
+    /// Change the weights of the heuristics, in the order they were added.
+    ///
+    /// Superfluous weights are ignored.
+    pub fn set_weights(&mut self, weights: &[f64]) {
+        for ((weight, _), new_weight) in self.terms.iter_mut().zip(weights) {
+            *weight = *new_weight;
+        }
+    }
+}
+
+impl Heuristic for Weighted {
+    fn evaluate(&self, position: &Position) -> Value {
+        let mut sum = 0.0;
+        for (weight, heuristic) in self.terms.iter().filter(|(weight, _)| *weight != 0.0) {
+            let value = heuristic.evaluate(position);
+            match value.as_stones() {
+                Some(stones) => sum += weight * stones,
+                None if *weight < 0.0 => return value.opposite(),
+                None => return value,
+            }
+        }
+        Value::estimate(sum)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::heuristic::{delta, stones_on_side};
+
+    #[test]
+    fn heuristics_are_summed_by_weight() {
+        let position = Position::from((3, 1, [1, 2, 3, 0, 1, 1]));
+        let heuristic = Weighted::new()
+            .with(2.0, delta())
+            .with(-1.0, stones_on_side());
+
+        assert_eq!(heuristic.evaluate(&position), Value::Estimate(0));
+    }
+
+    #[test]
+    fn infinite_values_dominate() {
+        let position = Position::new(3, 3);
+        let heuristic = Weighted::new()
+            .with(2.0, delta())
+            .with(-1.0, |_: &Position| Value::PositiveInfinity);
+
+        assert_eq!(heuristic.evaluate(&position), Value::NegativeInfinity);
+    }
+
+    #[test]
+    fn fractional_weights_are_kept() {
+        let position = Position::from((3, 0, [1, 0, 1, 0]));
+        let heuristic = Weighted::new().with(0.125, delta());
+
+        assert_eq!(heuristic.evaluate(&position), Value::Estimate(375));
+    }
+
+    #[test]
+    fn weights_can_be_changed() {
+        let mut heuristic = Weighted::new()
+            .with(2.0, delta())
+            .with(1.0, stones_on_side());
+
+        heuristic.set_weights(&[3.0, 4.5]);
+
+        assert_eq!(heuristic.weights(), vec![3.0, 4.5]);
+    }
+}
@@ -1,13 +1,17 @@
 //! Various strategies for playing Mancala
 
+pub mod analysis;
 pub mod heuristic;
 pub mod naive;
+pub mod report;
 pub mod tree;
 pub mod user;
 
 pub use self::{
-    heuristic::{Heuristic, Value},
+    analysis::{Analyse, Analysis},
+    heuristic::{Heuristic, Prior, Value},
     naive::{First, Random},
+    report::SearchReport,
     tree::{AlphaBeta, MinMax, MonteCarlo},
     user::user,
 };
@@ -17,10 +21,21 @@ use super::game::{Bowl, Position};
 pub trait Strategy {
     /// Return the play for this position
     fn play(&mut self, position: &Position) -> Option<Bowl>;
+
+    /// Report on the search behind the last play
+    ///
+    /// Strategies that do not search have nothing to report.
+    fn report(&self) -> Option<&SearchReport> {
+        None
+    }
 }
 
 impl Strategy for Box<dyn Strategy> {
     fn play(&mut self, position: &Position) -> Option<Bowl> {
         (**self).play(position)
     }
+
+    fn report(&self) -> Option<&SearchReport> {
+        (**self).report()
+    }
 }
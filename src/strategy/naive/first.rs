@@ -1,4 +1,4 @@
-///! The naive strategy to pick the first option.
+//! The naive strategy to pick the first option.
 use super::super::Strategy;
 use crate::game::{Bowl, Position};
 
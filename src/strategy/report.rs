@@ -0,0 +1,113 @@
+//! Reports on what a strategy was thinking.
+//!
+//! Strategies that search the game tree can report on their last search. A `SearchReport` tells how much work was
+//! done, how fast, and which line of play the strategy expects.
+//!
+//! ```
+//! use mancala::game::Position;
+//! use mancala::strategy::{AlphaBeta, Strategy};
+//! use mancala::strategy::tree::Depth;
+//!
+//! let mut strategy = AlphaBeta::strategy().limited_to(Depth::Limit(4)).build();
+//! let bowl = strategy.play(&Position::new(6, 4));
+//!
+//! let report = strategy.report().expect("a report of the last search");
+//! assert_eq!(report.principal_variation.first().copied(), bowl);
+//! ```
+
+use crate::{game::Bowl, strategy::heuristic::Value};
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+
+/// What a search found and what it took to find it.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct SearchReport {
+    /// The number of positions visited.
+    pub nodes: u64,
+    /// The number of times a search was cut off.
+    pub cutoffs: u64,
+    /// The number of positions found in the transposition table.
+    pub table_hits: u64,
+    /// The time the search took.
+    pub elapsed: Duration,
+    /// The value of the searched position, if the strategy determines one.
+    pub value: Option<Value>,
+    /// The expected line of play, starting with the chosen bowl.
+    pub principal_variation: Vec<Bowl>,
+}
+
+impl SearchReport {
+    /// Create an empty report.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of positions visited per second.
+    pub fn nodes_per_second(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds > 0.0 {
+            self.nodes as f64 / seconds
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Display for SearchReport {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(value) = self.value {
+            write!(formatter, "value: {} ", value)?;
+        }
+        write!(
+            formatter,
+            "nodes: {} ({:.0} nodes/s) cutoffs: {} table hits: {} time: {:?} pv:",
+            self.nodes,
+            self.nodes_per_second(),
+            self.cutoffs,
+            self.table_hits,
+            self.elapsed
+        )?;
+        for bowl in &self.principal_variation {
+            write!(formatter, " {}", bowl)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nodes_per_second_are_derived_from_elapsed_time() {
+        let report = SearchReport {
+            nodes: 300,
+            elapsed: Duration::from_millis(1500),
+            ..SearchReport::new()
+        };
+
+        assert_eq!(report.nodes_per_second(), 200.0);
+    }
+
+    #[test]
+    fn reports_without_elapsed_time_have_no_speed() {
+        assert_eq!(SearchReport::new().nodes_per_second(), 0.0);
+    }
+
+    #[test]
+    fn reports_are_displayed_on_a_single_line() {
+        let report = SearchReport {
+            nodes: 300,
+            cutoffs: 20,
+            table_hits: 5,
+            elapsed: Duration::from_secs(2),
+            value: Some(Value::Actual(4)),
+            principal_variation: vec![2, 0, 5],
+        };
+
+        assert_eq!(
+            report.to_string(),
+            "value: +4 nodes: 300 (150 nodes/s) cutoffs: 20 table hits: 5 time: 2s pv: 2 0 5"
+        );
+    }
+}
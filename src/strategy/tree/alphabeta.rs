@@ -12,35 +12,74 @@
 //!
 //! let strategy = AlphaBeta::strategy().limited_to(Depth::Limit(5)).with_heuristic(delta()).build();
 //! ```
+//!
+//! Positions that are reached by different orders of play are only searched once. Their results are kept in a
+//! transposition table, which size can be configured.
+//!
+//! ```
+//! use mancala::strategy::tree::{AlphaBeta, Depth};
+//!
+//! let strategy = AlphaBeta::strategy().limited_to(Depth::Limit(5)).with_table_size(1 << 20).build();
+//! ```
+//!
+//! The order in which plays are searched determines how much of the tree is cut off. By default plays are
+//! `Prioritized`, but any `PlayOrdering` can be used. The `statistics` keep track of how well the ordering works.
+//!
+//! ```
+//! use mancala::strategy::tree::{AlphaBeta, Depth};
+//! use mancala::strategy::tree::ordering::InOrder;
+//!
+//! let strategy = AlphaBeta::strategy().limited_to(Depth::Limit(5)).ordered_by(InOrder {}).build();
+//! ```
 
-use super::{Depth, DepthLimitedSearch};
+use super::{
+    ordering::{PlayOrdering, Prioritized},
+    table::{Bound, TranspositionTable},
+    Depth, DepthLimitedSearch, NODES_BETWEEN_DEADLINE_CHECKS,
+};
 use crate::{
     game::{Bowl, Position},
     strategy::{
+        analysis::{analyse_with, Analyse, Analysis},
         heuristic::{delta, Delta, Heuristic, Value},
-        Strategy,
+        SearchReport, Strategy,
     },
 };
 use std::cmp::max;
+use std::collections::HashSet;
+use std::fmt::{self, Display, Formatter};
+use std::time::Instant;
+
+const DEFAULT_TABLE_SIZE: usize = 1 << 20;
 
 /// Build AlphaBeta strategy instances
-pub struct AlphaBetaBuilder<H>
+pub struct AlphaBetaBuilder<H, O = Prioritized>
 where
     H: Heuristic + Sized,
+    O: PlayOrdering + Sized,
 {
     search_depth: Depth,
     heuristic: H,
+    table_size: usize,
+    ordering: O,
 }
 
-impl<H> AlphaBetaBuilder<H>
+impl<H, O> AlphaBetaBuilder<H, O>
 where
     H: Heuristic + Sized,
+    O: PlayOrdering + Sized,
 {
     /// Build an Alpha Beta strategy
-    pub fn build(self) -> AlphaBeta<H> {
+    pub fn build(self) -> AlphaBeta<H, O> {
         AlphaBeta {
             search_depth: self.search_depth,
             heuristic: self.heuristic,
+            table: TranspositionTable::with_size(self.table_size),
+            ordering: self.ordering,
+            preferred: None,
+            exhausted: false,
+            report: SearchReport::new(),
+            statistics: Statistics::new(),
         }
     }
 
@@ -51,114 +90,410 @@ where
     }
 
     /// with a certain heuristic
-    pub fn with_heuristic<H_>(self, heuristic: H_) -> AlphaBetaBuilder<H_>
+    pub fn with_heuristic<H_>(self, heuristic: H_) -> AlphaBetaBuilder<H_, O>
     where
         H_: Heuristic + Sized,
     {
         AlphaBetaBuilder {
             search_depth: self.search_depth,
             heuristic,
+            table_size: self.table_size,
+            ordering: self.ordering,
         }
     }
+
+    /// with a certain ordering of plays
+    pub fn ordered_by<O_>(self, ordering: O_) -> AlphaBetaBuilder<H, O_>
+    where
+        O_: PlayOrdering + Sized,
+    {
+        AlphaBetaBuilder {
+            search_depth: self.search_depth,
+            heuristic: self.heuristic,
+            table_size: self.table_size,
+            ordering,
+        }
+    }
+
+    /// with a transposition table of at most `bytes` in size, 0 disables the table
+    pub fn with_table_size(mut self, bytes: usize) -> Self {
+        self.table_size = bytes;
+        self
+    }
 }
 
 /// Pick the option that maximizes the minimum win, pruning sub-trees along the way.
-pub struct AlphaBeta<H>
+pub struct AlphaBeta<H, O = Prioritized>
 where
     H: Heuristic + Sized,
+    O: PlayOrdering + Sized,
 {
     search_depth: Depth,
     heuristic: H,
+    table: TranspositionTable,
+    ordering: O,
+    preferred: Option<Bowl>,
+    exhausted: bool,
+    report: SearchReport,
+    /// Statistics of all searches so far.
+    pub statistics: Statistics,
 }
 
 impl AlphaBeta<Delta> {
     /// Create a default AlphaBetaBuilder
     ///
-    /// It has an unlimited search depth and the Delta heuristic.
+    /// It has an unlimited search depth, the Delta heuristic, a transposition table of 1 MiB and prioritized plays.
     pub fn strategy() -> AlphaBetaBuilder<Delta> {
         AlphaBetaBuilder {
             search_depth: Depth::Infinite,
             heuristic: delta(),
+            table_size: DEFAULT_TABLE_SIZE,
+            ordering: Prioritized::new(),
         }
     }
 }
 
-impl<H> Strategy for AlphaBeta<H>
+impl<H, O> Strategy for AlphaBeta<H, O>
 where
     H: Heuristic + Sized,
+    O: PlayOrdering + Sized,
 {
     fn play(&mut self, position: &Position) -> Option<Bowl> {
         let search_depth = self.search_depth;
         let (bowl, _) = self.search(position, &search_depth);
         bowl
     }
+
+    fn report(&self) -> Option<&SearchReport> {
+        Some(&self.report)
+    }
 }
 
-impl<H> DepthLimitedSearch<Position, (Option<Bowl>, Value)> for AlphaBeta<H>
+impl<H, O> DepthLimitedSearch<Position, (Option<Bowl>, Value)> for AlphaBeta<H, O>
 where
     H: Heuristic + Sized,
+    O: PlayOrdering + Sized,
 {
     fn search(&mut self, position: &Position, search_depth: &Depth) -> (Option<Bowl>, Value) {
-        alpha_beta(
-            position,
+        self.search_with(position, search_depth, None)
+            .expect("search without deadline to complete")
+    }
+
+    fn search_until(
+        &mut self,
+        position: &Position,
+        search_depth: &Depth,
+        deadline: Instant,
+    ) -> Option<(Option<Bowl>, Value)> {
+        self.search_with(position, search_depth, Some(deadline))
+    }
+
+    fn prefer(&mut self, bowl: Bowl) {
+        self.preferred = Some(bowl);
+    }
+
+    fn exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    fn search_report(&self) -> Option<&SearchReport> {
+        Some(&self.report)
+    }
+}
+
+impl<H, O> Analyse for AlphaBeta<H, O>
+where
+    H: Heuristic + Sized,
+    O: PlayOrdering + Sized,
+{
+    fn analyse(&mut self, position: &Position, search_depth: &Depth, lines: usize) -> Analysis {
+        analyse_with(self, position, search_depth, lines)
+    }
+}
+
+impl<H, O> AlphaBeta<H, O>
+where
+    H: Heuristic + Sized,
+    O: PlayOrdering + Sized,
+{
+    fn search_with(
+        &mut self,
+        position: &Position,
+        search_depth: &Depth,
+        deadline: Option<Instant>,
+    ) -> Option<(Option<Bowl>, Value)> {
+        let start = Instant::now();
+        let mut root = *position;
+        self.table.next_generation();
+        self.ordering.start();
+        let mut search = Search::new(&self.heuristic, &mut self.table, &mut self.ordering);
+        search.deadline = deadline;
+        search.preferred = self.preferred.take();
+        let result = search.alpha_beta(
+            &mut root,
             Value::NegativeInfinity,
             Value::PositiveInfinity,
             search_depth,
-            &self.heuristic,
+        );
+        let (statistics, aborted) = (search.statistics, search.aborted);
+        self.exhausted = !aborted && !search.cut_by_depth;
+        self.statistics.add(&statistics);
+        self.report = SearchReport {
+            nodes: statistics.nodes,
+            cutoffs: statistics.cutoffs,
+            table_hits: statistics.table_hits,
+            elapsed: start.elapsed(),
+            ..SearchReport::new()
+        };
+        if aborted {
+            None
+        } else {
+            self.report.value = Some(result.1);
+            self.report.principal_variation =
+                self.principal_variation(position, result.0, search_depth);
+            Some(result)
+        }
+    }
+
+    /// Follow the best bowls in the transposition table, starting with `first`.
+    fn principal_variation(
+        &self,
+        position: &Position,
+        first: Option<Bowl>,
+        search_depth: &Depth,
+    ) -> Vec<Bowl> {
+        let mut line = vec![];
+        let mut position = *position;
+        let mut seen = HashSet::new();
+        let mut depth = *search_depth;
+        let mut next = first;
+        while let Some(bowl) = next {
+            if depth.is_zero() || !seen.insert(position.hash_key()) || position.make(bowl).is_err()
+            {
+                break;
+            }
+            line.push(bowl);
+            depth = depth.decrement();
+            next = self.table.probe(&position).and_then(|entry| entry.bowl);
+        }
+        line
+    }
+}
+
+/// Statistics about the searches of an `AlphaBeta` strategy.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub struct Statistics {
+    nodes: u64,
+    cutoffs: u64,
+    first_play_cutoffs: u64,
+    table_hits: u64,
+}
+
+impl Statistics {
+    /// Create statistics with all counts set to zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of positions searched.
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// The number of times a search was cut off.
+    pub fn cutoffs(&self) -> u64 {
+        self.cutoffs
+    }
+
+    /// The number of positions found in the transposition table.
+    pub fn table_hits(&self) -> u64 {
+        self.table_hits
+    }
+
+    /// The fraction of cutoffs that were caused by the first play searched.
+    ///
+    /// The closer to 1, the better the plays are ordered.
+    pub fn cutoff_rate(&self) -> f64 {
+        if self.cutoffs == 0 {
+            0.0
+        } else {
+            self.first_play_cutoffs as f64 / self.cutoffs as f64
+        }
+    }
+
+    fn add(&mut self, other: &Statistics) {
+        self.nodes += other.nodes;
+        self.cutoffs += other.cutoffs;
+        self.first_play_cutoffs += other.first_play_cutoffs;
+        self.table_hits += other.table_hits;
+    }
+}
+
+impl Display for Statistics {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "nodes: {} cutoffs: {} first play cutoffs: {:.1}%",
+            self.nodes,
+            self.cutoffs,
+            100.0 * self.cutoff_rate()
         )
     }
 }
 
-fn alpha_beta(
-    position: &Position,
-    alpha_prime: Value,
-    beta: Value,
-    search_depth: &Depth,
-    heuristic: &dyn Heuristic,
-) -> (Option<Bowl>, Value) {
-    let mut alpha = alpha_prime;
-    if position.finished() || search_depth.is_zero() {
+struct Search<'a> {
+    heuristic: &'a dyn Heuristic,
+    table: &'a mut TranspositionTable,
+    ordering: &'a mut dyn PlayOrdering,
+    deadline: Option<Instant>,
+    aborted: bool,
+    cut_by_depth: bool,
+    preferred: Option<Bowl>,
+    ply: usize,
+    statistics: Statistics,
+}
+
+impl<'a> Search<'a> {
+    fn new(
+        heuristic: &'a dyn Heuristic,
+        table: &'a mut TranspositionTable,
+        ordering: &'a mut dyn PlayOrdering,
+    ) -> Self {
+        Search {
+            heuristic,
+            table,
+            ordering,
+            deadline: None,
+            aborted: false,
+            cut_by_depth: false,
+            preferred: None,
+            ply: 0,
+            statistics: Statistics::new(),
+        }
+    }
+
+    fn out_of_time(&mut self) -> bool {
+        if !self.aborted
+            && self
+                .statistics
+                .nodes
+                .is_multiple_of(NODES_BETWEEN_DEADLINE_CHECKS as u64)
+        {
+            if let Some(deadline) = self.deadline {
+                self.aborted = Instant::now() >= deadline;
+            }
+        }
+        self.aborted
+    }
+
+    fn alpha_beta(
+        &mut self,
+        position: &mut Position,
+        alpha_prime: Value,
+        beta: Value,
+        search_depth: &Depth,
+    ) -> (Option<Bowl>, Value) {
+        self.statistics.nodes += 1;
+        if self.out_of_time() {
+            return (None, Value::NegativeInfinity);
+        }
         if position.finished() {
-            (
+            return (
                 None,
                 Value::Actual(position.score().expect("finished game to have a score")),
-            )
-        } else {
-            (None, heuristic.evaluate(position))
+            );
+        }
+        if search_depth.is_zero() {
+            self.cut_by_depth = true;
+            return (None, self.heuristic.evaluate(position));
+        }
+
+        let mut alpha = alpha_prime;
+        let mut hints = vec![];
+        if let Some(entry) = self.table.probe(position) {
+            self.statistics.table_hits += 1;
+            if entry.covers(search_depth) {
+                let usable = match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => entry.value >= beta,
+                    Bound::Upper => entry.value <= alpha,
+                };
+                if usable {
+                    // Entries of lines that ran to the end are stored with an infinite depth.
+                    self.cut_by_depth |= entry.depth != Depth::Infinite;
+                    return (entry.bowl, entry.value);
+                }
+            }
+            hints.extend(entry.bowl);
         }
-    } else {
+        hints.extend(self.preferred.take());
+
+        let mut options = position.options();
+        self.ordering.order(position, self.ply, &mut options);
+        for hint in hints {
+            if let Some(index) = options.iter().position(|bowl| *bowl == hint) {
+                options[..=index].rotate_right(1);
+            }
+        }
+
+        let cut_before = self.cut_by_depth;
+        self.cut_by_depth = false;
         let mut best_bowl = None;
         let mut best_value = Value::NegativeInfinity;
-        for bowl in position.options() {
-            let candidate_position = position.play(bowl).expect("option to be playable");
-            let value = if candidate_position.turn() == position.turn() {
-                let tuple = alpha_beta(
-                    &candidate_position,
-                    alpha,
-                    beta,
-                    &search_depth.decrement(),
-                    heuristic,
-                );
+        let player = position.turn();
+        for (index, bowl) in options.into_iter().enumerate() {
+            let undo = position.make(bowl).expect("option to be playable");
+            self.ply += 1;
+            let value = if position.turn() == player {
+                let tuple = self.alpha_beta(position, alpha, beta, &search_depth.decrement());
                 tuple.1
             } else {
-                let tuple = alpha_beta(
-                    &candidate_position,
+                let tuple = self.alpha_beta(
+                    position,
                     beta.opposite(),
                     alpha.opposite(),
                     &search_depth.decrement(),
-                    heuristic,
                 );
                 tuple.1.opposite()
             };
+            self.ply -= 1;
+            position.unmake(undo);
             if value > best_value {
                 best_bowl = Some(bowl);
                 best_value = value;
             }
             alpha = max(alpha, value);
+            if self.aborted {
+                break;
+            }
             if alpha >= beta {
+                self.statistics.cutoffs += 1;
+                if index == 0 {
+                    self.statistics.first_play_cutoffs += 1;
+                }
+                self.ordering.cutoff(position, self.ply, bowl, search_depth);
                 break;
             }
         }
+        if self.aborted {
+            return (None, Value::NegativeInfinity);
+        }
+
+        let bound = if best_value <= alpha_prime {
+            Bound::Upper
+        } else if best_value >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        let depth = if self.cut_by_depth {
+            *search_depth
+        } else {
+            Depth::Infinite
+        };
+        self.cut_by_depth |= cut_before;
+        self.table
+            .store(position, depth, best_value, bound, best_bowl);
         (best_bowl, best_value)
     }
 }
@@ -166,15 +501,54 @@ fn alpha_beta(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{game::Position, strategy::heuristic::delta};
+    use crate::{
+        game::Position,
+        strategy::{heuristic::delta, tree::ordering::InOrder},
+    };
+
+    fn alpha_beta(
+        position: &mut Position,
+        alpha: Value,
+        beta: Value,
+        search_depth: &Depth,
+        heuristic: &dyn Heuristic,
+    ) -> (Option<Bowl>, Value) {
+        let mut table = TranspositionTable::with_size(0);
+        let mut ordering = InOrder {};
+        Search::new(heuristic, &mut table, &mut ordering).alpha_beta(
+            position,
+            alpha,
+            beta,
+            search_depth,
+        )
+    }
+
+    fn nodes_searched(
+        position: &Position,
+        search_depth: &Depth,
+        table_size: usize,
+    ) -> (Value, usize) {
+        let mut position = *position;
+        let heuristic = delta();
+        let mut table = TranspositionTable::with_size(table_size);
+        let mut ordering = InOrder {};
+        let mut search = Search::new(&heuristic, &mut table, &mut ordering);
+        let (_, value) = search.alpha_beta(
+            &mut position,
+            Value::NegativeInfinity,
+            Value::PositiveInfinity,
+            search_depth,
+        );
+        (value, search.statistics.nodes() as usize)
+    }
 
     #[test]
     fn finished_games_are_scored() {
-        let position = Position::from((5, 0, [0, 0, 2, 2]));
+        let mut position = Position::from((5, 0, [0, 0, 2, 2]));
         let heuristic = delta();
 
         let (bowl, value) = alpha_beta(
-            &position,
+            &mut position,
             Value::NegativeInfinity,
             Value::PositiveInfinity,
             &Depth::Infinite,
@@ -187,11 +561,11 @@ mod tests {
 
     #[test]
     fn only_bowl_is_selected() {
-        let position = Position::from([1, 0, 1, 0]);
+        let mut position = Position::from([1, 0, 1, 0]);
         let heuristic = delta();
 
         let result = alpha_beta(
-            &position,
+            &mut position,
             Value::NegativeInfinity,
             Value::PositiveInfinity,
             &Depth::Infinite,
@@ -203,11 +577,11 @@ mod tests {
 
     #[test]
     fn best_bowl_is_selected() {
-        let position = Position::from([1, 2, 1, 0, 2, 1]);
+        let mut position = Position::from([1, 2, 1, 0, 2, 1]);
         let heuristic = delta();
 
         let (_, value) = alpha_beta(
-            &position,
+            &mut position,
             Value::NegativeInfinity,
             Value::PositiveInfinity,
             &Depth::Infinite,
@@ -216,4 +590,84 @@ mod tests {
 
         assert_eq!(value, Value::Actual(5));
     }
+
+    #[test]
+    fn best_bowl_is_selected_with_transposition_table() {
+        let position = Position::from([1, 2, 1, 0, 2, 1]);
+
+        let (value, nodes) = nodes_searched(&position, &Depth::Infinite, 1 << 16);
+        let (_, nodes_without_table) = nodes_searched(&position, &Depth::Infinite, 0);
+
+        assert_eq!(value, Value::Actual(5));
+        assert!(nodes <= nodes_without_table);
+    }
+
+    #[test]
+    fn transposition_table_cuts_node_count() {
+        let position = Position::new(4, 3);
+
+        let (value, nodes) = nodes_searched(&position, &Depth::Limit(9), 1 << 20);
+        let (expected, nodes_without_table) = nodes_searched(&position, &Depth::Limit(9), 0);
+
+        assert_eq!(value, expected);
+        assert!(nodes < nodes_without_table);
+    }
+
+    #[test]
+    fn strategy_agrees_with_search_without_table() {
+        let position = Position::from([1, 2, 1, 0, 2, 1]);
+        let mut strategy = AlphaBeta::strategy().build();
+
+        let (_, value) = strategy.search(&position, &Depth::Infinite);
+
+        assert_eq!(value, Value::Actual(5));
+    }
+
+    #[test]
+    fn report_contains_principal_variation() {
+        let position = Position::from([1, 2, 1, 0, 2, 1]);
+        let mut strategy = AlphaBeta::strategy().build();
+
+        let bowl = strategy.play(&position);
+        let report = strategy.report().expect("a report");
+
+        assert_eq!(report.value, Some(Value::Actual(5)));
+        assert_eq!(report.principal_variation.first().copied(), bowl);
+        assert!(report.nodes > 0);
+        let mut end = position;
+        for bowl in &report.principal_variation {
+            end.make(*bowl).expect("principal variation to be playable");
+        }
+        assert!(end.finished());
+    }
+
+    #[test]
+    fn prioritized_plays_improve_cutoff_rate() {
+        let position = Position::new(6, 4);
+        let depth = Depth::Limit(7);
+        let mut in_order = AlphaBeta::strategy()
+            .with_table_size(0)
+            .ordered_by(InOrder {})
+            .build();
+        let mut prioritized = AlphaBeta::strategy().with_table_size(0).build();
+
+        let (_, expected) = in_order.search(&position, &depth);
+        let (_, value) = prioritized.search(&position, &depth);
+
+        assert_eq!(value, expected);
+        assert!(prioritized.statistics.nodes() < in_order.statistics.nodes());
+        assert!(prioritized.statistics.cutoff_rate() > in_order.statistics.cutoff_rate());
+    }
+
+    #[test]
+    fn analysis_agrees_with_search() {
+        let position = Position::from([1, 2, 1, 0, 2, 1]);
+        let mut strategy = AlphaBeta::strategy().build();
+
+        let analysis = strategy.analyse(&position, &Depth::Infinite, 1);
+        let best = analysis.best().expect("a best line");
+
+        assert_eq!(best.value, Value::Actual(5));
+        assert_eq!(analysis.lines().len(), position.options().len());
+    }
 }
@@ -3,19 +3,93 @@
 //! The [ids search strategy](https://en.wikipedia.org/wiki/Iterative_deepening_depth-first_search) is a
 //!
 //! >  is a state space/graph search strategy in which a depth-limited version of depth-first search is run repeatedly with increasing depth limits until the goal is found.
+//!
+//! Any `DepthLimitedSearch` can be deepened iteratively. The deepest search that completed within the time budget
+//! determines the play. The best play of each search is searched first in the next, deeper, search. Deepening stops
+//! early when a search reached the end of every line, because a deeper search would find the same.
+//!
+//! The way to create a `IterativeDeepeningSearch` strategy is
+//!
+//! ```
+//! use mancala::strategy::tree::{AlphaBeta, Depth, IterativeDeepeningSearch};
+//! use std::time::Duration;
+//!
+//! let strategy = IterativeDeepeningSearch::strategy(AlphaBeta::strategy().build())
+//!     .limited_to(Depth::Limit(12))
+//!     .thinking_for(Duration::from_millis(500))
+//!     .build();
+//! ```
 
 use super::{Depth, DepthLimitedSearch};
 use crate::{
     game::{Bowl, Position},
-    strategy::{heuristic::Value, Strategy},
+    strategy::{heuristic::Value, SearchReport, Strategy},
 };
+use std::time::{Duration, Instant};
+
+/// Build IterativeDeepeningSearch strategy instances
+pub struct IterativeDeepeningSearchBuilder<S>
+where
+    S: DepthLimitedSearch<Position, (Option<Bowl>, Value)> + Sized,
+{
+    max_depth: Depth,
+    budget: Duration,
+    searcher: S,
+}
+
+impl<S> IterativeDeepeningSearchBuilder<S>
+where
+    S: DepthLimitedSearch<Position, (Option<Bowl>, Value)> + Sized,
+{
+    /// Build an iterative deepening search strategy
+    pub fn build(self) -> IterativeDeepeningSearch<S> {
+        IterativeDeepeningSearch {
+            max_depth: self.max_depth,
+            budget: self.budget,
+            searcher: self.searcher,
+            report: SearchReport::new(),
+        }
+    }
+
+    /// limited to a certain search depth
+    pub fn limited_to(mut self, max_depth: Depth) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
 
-struct IterativeDeepeningSearch<S>
+    /// thinking at most `budget` per play
+    pub fn thinking_for(mut self, budget: Duration) -> Self {
+        self.budget = budget;
+        self
+    }
+}
+
+/// Search deeper and deeper until time runs out, picking the option of the deepest completed search.
+pub struct IterativeDeepeningSearch<S>
 where
     S: DepthLimitedSearch<Position, (Option<Bowl>, Value)> + Sized,
 {
     max_depth: Depth,
+    budget: Duration,
     searcher: S,
+    report: SearchReport,
+}
+
+impl<S> IterativeDeepeningSearch<S>
+where
+    S: DepthLimitedSearch<Position, (Option<Bowl>, Value)> + Sized,
+{
+    /// Create a default IterativeDeepeningSearchBuilder around `searcher`
+    ///
+    /// It has an unlimited search depth and thinks for one second per play. The search of depth 1 is always
+    /// completed, even when it exceeds the budget.
+    pub fn strategy(searcher: S) -> IterativeDeepeningSearchBuilder<S> {
+        IterativeDeepeningSearchBuilder {
+            max_depth: Depth::Infinite,
+            budget: Duration::from_secs(1),
+            searcher,
+        }
+    }
 }
 
 impl<S> Strategy for IterativeDeepeningSearch<S>
@@ -23,14 +97,170 @@ where
     S: DepthLimitedSearch<Position, (Option<Bowl>, Value)> + Sized,
 {
     fn play(&mut self, position: &Position) -> Option<Bowl> {
-        let (mut best_bowl, mut best_value) = (None, Value::NegativeInfinity);
-        for current_depth in Depth::Limit(1).to(self.max_depth) {
-            let (candidate_bowl, candidate_value) = self.searcher.search(position, &current_depth);
-            if candidate_value > best_value {
-                best_bowl = candidate_bowl;
-                best_value = candidate_value;
+        let start = Instant::now();
+        let deadline = start + self.budget;
+        self.report = SearchReport::new();
+        let (mut best_bowl, _) = self.searcher.search(position, &Depth::Limit(1));
+        self.take_report(true);
+        let mut current_depth = Depth::Limit(1);
+        while best_bowl.is_some()
+            && !self.searcher.exhausted()
+            && shallower(&current_depth, &self.max_depth)
+            && Instant::now() < deadline
+        {
+            current_depth = current_depth.increment();
+            if let Some(bowl) = best_bowl {
+                self.searcher.prefer(bowl);
+            }
+            let result = self
+                .searcher
+                .search_until(position, &current_depth, deadline);
+            self.take_report(result.is_some());
+            match result {
+                Some((bowl, _)) => best_bowl = bowl,
+                None => break,
             }
         }
+        self.report.elapsed = start.elapsed();
         best_bowl
     }
+
+    fn report(&self) -> Option<&SearchReport> {
+        Some(&self.report)
+    }
+}
+
+impl<S> IterativeDeepeningSearch<S>
+where
+    S: DepthLimitedSearch<Position, (Option<Bowl>, Value)> + Sized,
+{
+    /// Add the work of the last iteration to the report, and its findings when it completed.
+    fn take_report(&mut self, completed: bool) {
+        if let Some(iteration) = self.searcher.search_report() {
+            self.report.nodes += iteration.nodes;
+            self.report.cutoffs += iteration.cutoffs;
+            self.report.table_hits += iteration.table_hits;
+            if completed {
+                self.report.value = iteration.value;
+                self.report
+                    .principal_variation
+                    .clone_from(&iteration.principal_variation);
+            }
+        }
+    }
+}
+
+/// Determine if `depth` is less than `max_depth`.
+fn shallower(depth: &Depth, max_depth: &Depth) -> bool {
+    match (depth, max_depth) {
+        (Depth::Limit(depth), Depth::Limit(max_depth)) => depth < max_depth,
+        (Depth::Limit(_), Depth::Infinite) => true,
+        (Depth::Infinite, _) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::tree::{AlphaBeta, MinMax};
+
+    #[test]
+    fn finished_games_have_no_play() {
+        let position = Position::from((5, 0, [0, 0, 2, 2]));
+        let mut strategy =
+            IterativeDeepeningSearch::strategy(AlphaBeta::strategy().build()).build();
+
+        assert_eq!(strategy.play(&position), None);
+    }
+
+    #[test]
+    fn deepest_search_is_trusted() {
+        let position = Position::new(4, 3);
+        let depth = Depth::Limit(7);
+        let (expected, _) = MinMax::new().search(&position, &depth);
+        let mut strategy = IterativeDeepeningSearch::strategy(MinMax::new())
+            .limited_to(depth)
+            .thinking_for(Duration::from_secs(60))
+            .build();
+
+        assert_eq!(strategy.play(&position), expected);
+    }
+
+    #[test]
+    fn search_stops_when_time_runs_out() {
+        let position = Position::new(6, 4);
+        let mut strategy = IterativeDeepeningSearch::strategy(AlphaBeta::strategy().build())
+            .thinking_for(Duration::from_millis(50))
+            .build();
+
+        let start = Instant::now();
+        let bowl = strategy.play(&position);
+
+        assert!(bowl.is_some());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn minmax_stops_when_time_runs_out() {
+        let position = Position::new(6, 4);
+        let mut strategy = IterativeDeepeningSearch::strategy(MinMax::new())
+            .thinking_for(Duration::from_millis(50))
+            .build();
+
+        let start = Instant::now();
+        let bowl = strategy.play(&position);
+
+        assert!(bowl.is_some());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn solved_positions_are_not_deepened() {
+        let position = Position::new(2, 2);
+        let mut strategy = IterativeDeepeningSearch::strategy(AlphaBeta::strategy().build())
+            .thinking_for(Duration::from_secs(10))
+            .build();
+
+        let start = Instant::now();
+        let bowl = strategy.play(&position);
+
+        assert!(bowl.is_some());
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(strategy.report().and_then(|report| report.value).is_some());
+    }
+
+    #[test]
+    fn depth_zero_searches_once() {
+        let position = Position::new(6, 4);
+        let mut strategy = IterativeDeepeningSearch::strategy(MinMax::new())
+            .limited_to(Depth::Limit(0))
+            .thinking_for(Duration::from_secs(10))
+            .build();
+
+        let start = Instant::now();
+        let bowl = strategy.play(&position);
+
+        assert!(bowl.is_some());
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn report_covers_deepest_search() {
+        let position = Position::new(4, 3);
+        let depth = Depth::Limit(6);
+        let mut searcher = MinMax::new();
+        searcher.search(&position, &depth);
+        let expected = searcher.report().expect("a report").clone();
+        let mut strategy = IterativeDeepeningSearch::strategy(MinMax::new())
+            .limited_to(depth)
+            .thinking_for(Duration::from_secs(60))
+            .build();
+
+        strategy.play(&position);
+        let report = strategy.report().expect("a report");
+
+        assert_eq!(report.value, expected.value);
+        assert_eq!(report.principal_variation, expected.principal_variation);
+        assert!(report.nodes > expected.nodes);
+    }
 }
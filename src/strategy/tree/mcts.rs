@@ -4,216 +4,762 @@
 //!
 //! > a heuristic search algorithm for some kinds of decision processes, most notably those employed in software that plays board games. In that context MCTS is used to solve the game tree.
 //!
+//! This implementation uses the upper confidence bound for trees, UCT. Each simulation selects a path through the
+//! tree, expands it with a single node and plays out the game from there. The result of the playout is propagated back
+//! along the path. After all simulations the most visited play is picked.
+//!
+//! Playouts pick random plays, or when guided by the heuristic, the play the heuristic likes best. A playout that is
+//! limited in depth is scored by the heuristic when the limit is reached.
+//!
+//! The value in the report is the expected result of the chosen play, scaled to stones like the values of other
+//! searches. It ranges from minus all the stones in the game for a certain loss to all the stones for a certain win.
+//!
+//! With a `Prior`, the tree expands the most promising play first, and selects with PUCT instead of UCT. The
+//! exploration of a play is then in proportion to its prior.
+//!
+//! When leaves are valued, there are no playouts. Instead the heuristic values the expanded leaf, as a fraction of all
+//! stones. Together with a prior this is the search of `Zero`, where a network supplies both.
+//!
 //! The way to create a `MonteCarlo` strategy is
 //!
 //! ```
 //! use mancala::strategy::tree::{MonteCarlo, Depth};
 //! use mancala::strategy::heuristic::delta;
 //!
-//! let strategy = MonteCarlo::strategy().limited_to(Depth::Limit(5)).with_heuristic(delta()).build();
+//! let strategy = MonteCarlo::strategy()
+//!     .simulations(500)
+//!     .exploration(1.0)
+//!     .seeded(37)
+//!     .limited_to(Depth::Limit(5))
+//!     .with_heuristic(delta())
+//!     .build();
 //! ```
 
-use super::{Depth, DepthLimitedSearch};
+use super::Depth;
 use crate::{
-    game::{Bowl, Position},
+    game::{Bowl, Player, Position},
     strategy::{
-        heuristic::{delta, Delta, Heuristic},
-        Strategy, Value,
+        heuristic::{delta, Delta, Heuristic, Prior, Uniform},
+        SearchReport, Strategy, Value,
     },
 };
-use std::cmp::max;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use std::cmp::Ordering;
+use std::time::Instant;
 
 /// Build MonteCarlo strategy instances
-pub struct MonteCarloBuilder<H>
+pub struct MonteCarloBuilder<H, P = Uniform>
 where
     H: Heuristic + Sized,
+    P: Prior + Sized,
 {
+    simulations: usize,
+    exploration: f64,
+    seed: Option<u64>,
+    guided: bool,
+    valued: bool,
     search_depth: Depth,
     heuristic: H,
+    prior: Option<P>,
 }
 
-impl<H> MonteCarloBuilder<H>
+impl<H, P> MonteCarloBuilder<H, P>
 where
     H: Heuristic + Sized,
+    P: Prior + Sized,
 {
-    /// Build an Alpha Beta strategy
-    pub fn build(self) -> MonteCarlo<H> {
+    /// Build a Monte Carlo strategy
+    pub fn build(self) -> MonteCarlo<H, P> {
+        let rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
         MonteCarlo {
+            simulations: self.simulations,
+            exploration: self.exploration,
+            guided: self.guided,
+            valued: self.valued,
             search_depth: self.search_depth,
             heuristic: self.heuristic,
+            prior: self.prior,
+            rng,
+            report: SearchReport::new(),
         }
     }
 
-    /// limited to a certain search depth
+    /// with a certain number of simulations per play, at least one
+    pub fn simulations(mut self, simulations: usize) -> Self {
+        self.simulations = simulations;
+        self
+    }
+
+    /// with a certain exploration constant
+    pub fn exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    /// with a random number generator seeded by `seed`, making the strategy deterministic
+    pub fn seeded(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// with playouts guided by the heuristic instead of random plays
+    pub fn guided(mut self) -> Self {
+        self.guided = true;
+        self
+    }
+
+    /// with leaves valued by the heuristic instead of played out
+    pub fn valued(mut self) -> Self {
+        self.valued = true;
+        self
+    }
+
+    /// with playouts limited to a certain depth
     pub fn limited_to(mut self, search_depth: Depth) -> Self {
         self.search_depth = search_depth;
         self
     }
 
     /// with a certain heuristic
-    pub fn with_heuristic<H_>(self, heuristic: H_) -> MonteCarloBuilder<H_>
+    pub fn with_heuristic<H_>(self, heuristic: H_) -> MonteCarloBuilder<H_, P>
     where
         H_: Heuristic + Sized,
     {
         MonteCarloBuilder {
+            simulations: self.simulations,
+            exploration: self.exploration,
+            seed: self.seed,
+            guided: self.guided,
+            valued: self.valued,
             search_depth: self.search_depth,
             heuristic,
+            prior: self.prior,
+        }
+    }
+
+    /// with a prior, selecting plays with PUCT
+    pub fn with_prior<P_>(self, prior: P_) -> MonteCarloBuilder<H, P_>
+    where
+        P_: Prior + Sized,
+    {
+        MonteCarloBuilder {
+            simulations: self.simulations,
+            exploration: self.exploration,
+            seed: self.seed,
+            guided: self.guided,
+            valued: self.valued,
+            search_depth: self.search_depth,
+            heuristic: self.heuristic,
+            prior: Some(prior),
         }
     }
 }
 
 /// Pick the option that maximizes wins after random rollouts.
-pub struct MonteCarlo<H>
+pub struct MonteCarlo<H, P = Uniform>
 where
     H: Heuristic + Sized,
+    P: Prior + Sized,
 {
+    simulations: usize,
+    exploration: f64,
+    guided: bool,
+    valued: bool,
     search_depth: Depth,
     heuristic: H,
+    prior: Option<P>,
+    rng: StdRng,
+    report: SearchReport,
 }
 
 impl MonteCarlo<Delta> {
-    /// Create a default AlphaBetaBuilder
+    /// Create a default MonteCarloBuilder
     ///
-    /// It has an unlimited search depth and the Delta heuristic.
+    /// It runs 1000 simulations per play with an exploration constant of √2. Playouts are random, have an unlimited
+    /// depth and the Delta heuristic. There is no prior.
     pub fn strategy() -> MonteCarloBuilder<Delta> {
         MonteCarloBuilder {
+            simulations: 1000,
+            exploration: std::f64::consts::SQRT_2,
+            seed: None,
+            guided: false,
+            valued: false,
             search_depth: Depth::Infinite,
             heuristic: delta(),
+            prior: None,
         }
     }
 }
 
-impl<H> Strategy for MonteCarlo<H>
+impl<H, P> Strategy for MonteCarlo<H, P>
 where
     H: Heuristic + Sized,
+    P: Prior + Sized,
 {
     fn play(&mut self, position: &Position) -> Option<Bowl> {
-        let search_depth = self.search_depth;
-        let (bowl, _) = self.search(position, &search_depth);
+        let start = Instant::now();
+        self.report = SearchReport::new();
+        if position.finished() {
+            return None;
+        }
+        let tree = self.search(position);
+        let bowl = tree.most_visited_play();
+        self.report_on(&tree, bowl, start);
         bowl
     }
+
+    fn report(&self) -> Option<&SearchReport> {
+        Some(&self.report)
+    }
 }
 
-impl<H> DepthLimitedSearch<Position, (Option<Bowl>, Value)> for MonteCarlo<H>
+impl<H, P> MonteCarlo<H, P>
 where
     H: Heuristic + Sized,
+    P: Prior + Sized,
 {
-    fn search(&mut self, position: &Position, search_depth: &Depth) -> (Option<Bowl>, Value) {
-        alpha_beta(
-            position,
-            Value::NegativeInfinity,
-            Value::PositiveInfinity,
-            search_depth,
-            &self.heuristic,
-        )
+    /// The heuristic that values playouts, or leaves.
+    pub(crate) fn heuristic(&self) -> &H {
+        &self.heuristic
     }
-}
 
-fn alpha_beta(
-    position: &Position,
-    alpha_prime: Value,
-    beta: Value,
-    search_depth: &Depth,
-    heuristic: &dyn Heuristic,
-) -> (Option<Bowl>, Value) {
-    let mut alpha = alpha_prime;
-    if position.finished() || search_depth.is_zero() {
-        if position.finished() {
-            (
-                None,
-                Value::Actual(position.score().expect("finished game to have a score")),
-            )
+    /// Run all simulations from `position`, counting the nodes in the report.
+    pub(crate) fn search(&mut self, position: &Position) -> Tree {
+        let mut tree = Tree::new(*position, self.prior.as_ref());
+        for _ in 0..self.simulations.max(1) {
+            self.simulate(&mut tree);
+        }
+        tree
+    }
+
+    /// Complete the report on a search that picked `bowl`.
+    pub(crate) fn report_on(&mut self, tree: &Tree, bowl: Option<Bowl>, start: Instant) {
+        let position = tree.nodes[0].position;
+        self.report.elapsed = start.elapsed();
+        self.report.principal_variation = tree.principal_variation();
+        self.report.value = bowl.and_then(|bowl| tree.child(0, bowl)).map(|child| {
+            let node = &tree.nodes[child];
+            let expected = 2.0 * node.value / node.visits as f64 - 1.0;
+            Value::estimate(expected * position.total() as f64)
+        });
+    }
+
+    fn simulate(&mut self, tree: &mut Tree) {
+        let mut path = vec![0];
+        let mut current = 0;
+        while tree.nodes[current].untried.is_empty() && !tree.nodes[current].children.is_empty() {
+            current = match self.prior {
+                Some(_) => tree.select_by_prior(current, self.exploration),
+                None => tree.select(current, self.exploration),
+            };
+            path.push(current);
+        }
+        let prior = self.prior.as_ref();
+        if let Some(child) = tree.expand(current, &mut self.rng, prior) {
+            current = child;
+            path.push(current);
+        }
+        self.report.nodes += path.len() as u64;
+        let leaf = tree.nodes[current].position;
+        let reward = if self.valued {
+            self.value(&leaf)
         } else {
-            (None, heuristic.evaluate(position))
-        }
-    } else {
-        let mut best_bowl = None;
-        let mut best_value = Value::NegativeInfinity;
-        for bowl in position.options() {
-            let candidate_position = position.play(bowl).expect("option to be playable");
-            let value = if candidate_position.turn() == position.turn() {
-                let tuple = alpha_beta(
-                    &candidate_position,
-                    alpha,
-                    beta,
-                    &search_depth.decrement(),
-                    heuristic,
-                );
-                tuple.1
-            } else {
-                let tuple = alpha_beta(
-                    &candidate_position,
-                    beta.opposite(),
-                    alpha.opposite(),
-                    &search_depth.decrement(),
-                    heuristic,
-                );
-                tuple.1.opposite()
+            self.playout(leaf).reward(Player::Red)
+        };
+        for index in path {
+            let node = &mut tree.nodes[index];
+            node.visits += 1;
+            node.value += match node.mover {
+                Player::Red => reward,
+                Player::Blue => 1.0 - reward,
             };
-            if value > best_value {
-                best_bowl = Some(bowl);
-                best_value = value;
+        }
+    }
+
+    /// The reward of `position` for `Red`, from its value as a fraction of all stones.
+    fn value(&self, position: &Position) -> f64 {
+        let value = match position.score() {
+            Some(score) => Value::Actual(score),
+            None => self.heuristic.evaluate(position),
+        };
+        let fraction = match value.as_stones() {
+            Some(stones) => (stones / position.total().max(1) as f64).clamp(-1.0, 1.0),
+            None if value > Value::Actual(0) => 1.0,
+            None => -1.0,
+        };
+        let reward = (1.0 + fraction) / 2.0;
+        match position.turn() {
+            Player::Red => reward,
+            Player::Blue => 1.0 - reward,
+        }
+    }
+
+    fn playout(&mut self, start: Position) -> Playout {
+        let mut position = start;
+        let mut depth = self.search_depth;
+        while !position.finished() {
+            if depth.is_zero() {
+                let value = self.heuristic.evaluate(&position);
+                return Playout::from_value(position.turn(), value);
             }
-            alpha = max(alpha, value);
-            if alpha >= beta {
-                break;
+            let bowl = self.pick(&position);
+            position = position.play(bowl).expect("option to be playable");
+            depth = depth.decrement();
+            self.report.nodes += 1;
+        }
+        let score = position
+            .score_for(Player::Red)
+            .expect("finished game to have a score");
+        Playout::from_value(Player::Red, Value::Actual(score))
+    }
+
+    fn pick(&mut self, position: &Position) -> Bowl {
+        let mut options = position.options();
+        options.shuffle(&mut self.rng);
+        if self.guided {
+            let heuristic = &self.heuristic;
+            let value = |bowl: &Bowl| {
+                let candidate = position.play(*bowl).expect("option to be playable");
+                let value = heuristic.evaluate(&candidate);
+                if candidate.turn() == position.turn() {
+                    value
+                } else {
+                    value.opposite()
+                }
+            };
+            *options
+                .iter()
+                .max_by_key(|bowl| value(bowl))
+                .expect("unfinished position to have options")
+        } else {
+            options[0]
+        }
+    }
+}
+
+/// The result of a playout, from the perspective of `Red`.
+#[derive(Debug, PartialEq, Copy, Clone)]
+enum Playout {
+    Win,
+    Draw,
+    Loss,
+}
+
+impl Playout {
+    fn from_value(player: Player, value: Value) -> Self {
+        let result = if value > Value::Actual(0) {
+            Playout::Win
+        } else if value < Value::Estimate(0) {
+            Playout::Loss
+        } else {
+            Playout::Draw
+        };
+        match player {
+            Player::Red => result,
+            Player::Blue => result.opposite(),
+        }
+    }
+
+    fn opposite(self) -> Self {
+        match self {
+            Playout::Win => Playout::Loss,
+            Playout::Draw => Playout::Draw,
+            Playout::Loss => Playout::Win,
+        }
+    }
+
+    fn reward(self, player: Player) -> f64 {
+        let result = match player {
+            Player::Red => self,
+            Player::Blue => self.opposite(),
+        };
+        match result {
+            Playout::Win => 1.0,
+            Playout::Draw => 0.5,
+            Playout::Loss => 0.0,
+        }
+    }
+}
+
+pub(crate) struct Node {
+    position: Position,
+    bowl: Option<Bowl>,
+    mover: Player,
+    children: Vec<usize>,
+    untried: Vec<Bowl>,
+    visits: u32,
+    value: f64,
+    /// The prior of the plays of this node, if there is a prior.
+    priors: Vec<f64>,
+}
+
+impl Node {
+    fn new<P>(position: Position, bowl: Option<Bowl>, mover: Player, prior: Option<&P>) -> Self
+    where
+        P: Prior,
+    {
+        let (untried, priors) = if position.finished() {
+            (vec![], vec![])
+        } else {
+            let priors = prior
+                .map(|prior| prior.priors(&position))
+                .unwrap_or_default();
+            (position.options(), priors)
+        };
+        Node {
+            position,
+            bowl,
+            mover,
+            children: vec![],
+            untried,
+            visits: 0,
+            value: 0.0,
+            priors,
+        }
+    }
+
+    fn prior(&self, bowl: Bowl) -> f64 {
+        self.priors.get(bowl).copied().unwrap_or(0.0)
+    }
+}
+
+/// The tree of a search, with the position searched at its root.
+pub(crate) struct Tree {
+    nodes: Vec<Node>,
+}
+
+impl Tree {
+    fn new<P>(position: Position, prior: Option<&P>) -> Self
+    where
+        P: Prior,
+    {
+        let root = Node::new(position, None, position.turn().other(), prior);
+        Tree { nodes: vec![root] }
+    }
+
+    fn select(&self, parent: usize, exploration: f64) -> usize {
+        let log_visits = (self.nodes[parent].visits as f64).ln();
+        let uct = |child: &usize| {
+            let node = &self.nodes[*child];
+            let visits = node.visits as f64;
+            node.value / visits + exploration * (log_visits / visits).sqrt()
+        };
+        *self.nodes[parent]
+            .children
+            .iter()
+            .max_by(|left, right| {
+                uct(left)
+                    .partial_cmp(&uct(right))
+                    .unwrap_or(Ordering::Equal)
+            })
+            .expect("node to have children")
+    }
+
+    /// Select the child with the best PUCT value, which weighs exploration by the prior of the play.
+    fn select_by_prior(&self, parent: usize, exploration: f64) -> usize {
+        let parent = &self.nodes[parent];
+        let sqrt_visits = (parent.visits as f64).sqrt();
+        let puct = |child: &usize| {
+            let node = &self.nodes[*child];
+            let visits = node.visits as f64;
+            let prior = node.bowl.map(|bowl| parent.prior(bowl)).unwrap_or(0.0);
+            node.value / visits + exploration * prior * sqrt_visits / (1.0 + visits)
+        };
+        *parent
+            .children
+            .iter()
+            .max_by(|left, right| {
+                puct(left)
+                    .partial_cmp(&puct(right))
+                    .unwrap_or(Ordering::Equal)
+            })
+            .expect("node to have children")
+    }
+
+    /// Expand an untried play, the most promising one when there is a prior.
+    fn expand<P>(&mut self, parent: usize, rng: &mut StdRng, prior: Option<&P>) -> Option<usize>
+    where
+        P: Prior,
+    {
+        let node = &mut self.nodes[parent];
+        if node.untried.is_empty() {
+            return None;
+        }
+        node.untried.shuffle(rng);
+        let index = if node.priors.is_empty() {
+            node.untried.len() - 1
+        } else {
+            let node = &*node;
+            (0..node.untried.len())
+                .max_by(|left, right| {
+                    node.prior(node.untried[*left])
+                        .partial_cmp(&node.prior(node.untried[*right]))
+                        .unwrap_or(Ordering::Equal)
+                })
+                .expect("an untried play")
+        };
+        let bowl = node.untried.swap_remove(index);
+        let position = node.position;
+        let child = Node::new(
+            position.play(bowl).expect("option to be playable"),
+            Some(bowl),
+            position.turn(),
+            prior,
+        );
+        self.nodes.push(child);
+        let index = self.nodes.len() - 1;
+        self.nodes[parent].children.push(index);
+        Some(index)
+    }
+
+    /// The most visited child of `parent`, the one with the highest prior in case of a tie.
+    fn most_visited(&self, parent: usize) -> Option<usize> {
+        let parent = &self.nodes[parent];
+        let key = |child: &usize| {
+            let node = &self.nodes[*child];
+            let prior = node.bowl.map(|bowl| parent.prior(bowl)).unwrap_or(0.0);
+            (node.visits, prior)
+        };
+        parent.children.iter().copied().max_by(|left, right| {
+            key(left)
+                .partial_cmp(&key(right))
+                .unwrap_or(Ordering::Equal)
+        })
+    }
+
+    /// The most visited play of the root.
+    pub(crate) fn most_visited_play(&self) -> Option<Bowl> {
+        self.most_visited(0)
+            .and_then(|child| self.nodes[child].bowl)
+    }
+
+    /// The fraction of visits of every bowl of the root.
+    ///
+    /// When no play of the root is visited, the priors are used instead.
+    pub(crate) fn visits(&self) -> Vec<f64> {
+        let root = &self.nodes[0];
+        let total: u32 = root
+            .children
+            .iter()
+            .map(|child| self.nodes[*child].visits)
+            .sum();
+        if total == 0 {
+            return (0..root.position.size())
+                .map(|bowl| root.prior(bowl))
+                .collect();
+        }
+        let mut visits = vec![0.0; root.position.size()];
+        for child in &root.children {
+            let node = &self.nodes[*child];
+            if let Some(bowl) = node.bowl {
+                visits[bowl] = node.visits as f64 / total as f64;
             }
         }
-        (best_bowl, best_value)
+        visits
+    }
+
+    fn child(&self, parent: usize, bowl: Bowl) -> Option<usize> {
+        self.nodes[parent]
+            .children
+            .iter()
+            .copied()
+            .find(|child| self.nodes[*child].bowl == Some(bowl))
+    }
+
+    /// Follow the most visited children from the root.
+    fn principal_variation(&self) -> Vec<Bowl> {
+        let mut line = vec![];
+        let mut current = 0;
+        while let Some(child) = self.most_visited(current) {
+            line.extend(self.nodes[child].bowl);
+            current = child;
+        }
+        line
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{game::Position, strategy::heuristic::delta};
+    use crate::game::Position;
+    use crate::strategy::heuristic::uniform;
 
     #[test]
-    fn finished_games_are_scored() {
+    fn finished_games_have_no_play() {
         let position = Position::from((5, 0, [0, 0, 2, 2]));
-        let heuristic = delta();
-
-        let (bowl, value) = alpha_beta(
-            &position,
-            Value::NegativeInfinity,
-            Value::PositiveInfinity,
-            &Depth::Infinite,
-            &heuristic,
-        );
+        let mut strategy = MonteCarlo::strategy().seeded(37).build();
 
-        assert_eq!(value, Value::Actual(1));
-        assert_eq!(bowl, None);
+        assert_eq!(strategy.play(&position), None);
     }
 
     #[test]
     fn only_bowl_is_selected() {
         let position = Position::from([1, 0, 1, 0]);
-        let heuristic = delta();
-
-        let result = alpha_beta(
-            &position,
-            Value::NegativeInfinity,
-            Value::PositiveInfinity,
-            &Depth::Infinite,
-            &heuristic,
-        );
+        let mut strategy = MonteCarlo::strategy().seeded(37).build();
 
-        assert_eq!(result, (Some(0), Value::Actual(2)));
+        assert_eq!(strategy.play(&position), Some(0));
     }
 
     #[test]
-    fn best_bowl_is_selected() {
-        let position = Position::from([1, 2, 1, 0, 2, 1]);
-        let heuristic = delta();
-
-        let (_, value) = alpha_beta(
-            &position,
-            Value::NegativeInfinity,
-            Value::PositiveInfinity,
-            &Depth::Infinite,
-            &heuristic,
-        );
+    fn winning_bowl_is_selected() {
+        let position = Position::from([1, 3, 0, 5, 0, 1]);
+        let mut strategy = MonteCarlo::strategy().seeded(37).build();
+
+        assert_eq!(strategy.play(&position), Some(0));
+    }
+
+    #[test]
+    fn guided_playouts_select_winning_bowl() {
+        let position = Position::from([1, 3, 0, 5, 0, 1]);
+        let mut strategy = MonteCarlo::strategy()
+            .simulations(200)
+            .guided()
+            .seeded(37)
+            .build();
+
+        assert_eq!(strategy.play(&position), Some(0));
+    }
+
+    #[test]
+    fn at_least_one_simulation_is_run() {
+        let position = Position::new(3, 2);
+        let mut strategy = MonteCarlo::strategy().simulations(0).seeded(37).build();
+
+        let bowl = strategy.play(&position);
+
+        assert!(bowl
+            .map(|bowl| position.options().contains(&bowl))
+            .unwrap_or(false));
+    }
+
+    #[test]
+    fn seeded_strategies_are_deterministic() {
+        let position = Position::new(6, 4);
+        let mut first = MonteCarlo::strategy().simulations(100).seeded(37).build();
+        let mut second = MonteCarlo::strategy().simulations(100).seeded(37).build();
+
+        assert_eq!(first.play(&position), second.play(&position));
+    }
+
+    #[test]
+    fn report_follows_most_visited_plays() {
+        let position = Position::new(4, 3);
+        let mut strategy = MonteCarlo::strategy().simulations(200).seeded(37).build();
+
+        let bowl = strategy.play(&position);
+        let report = strategy.report().expect("a report");
+
+        assert_eq!(report.principal_variation.first().copied(), bowl);
+        assert!(report.value.is_some());
+        assert!(report.nodes >= 200);
+    }
+
+    #[test]
+    fn report_values_a_winning_bowl() {
+        let position = Position::from([1, 3, 0, 5, 0, 1]);
+        let mut strategy = MonteCarlo::strategy().seeded(37).build();
+
+        strategy.play(&position);
+        let value = strategy.report().and_then(|report| report.value);
+
+        assert!(value > Some(Value::Actual(0)));
+        assert!(value <= Some(Value::Actual(10)));
+    }
+
+    #[test]
+    fn priors_steer_the_search() {
+        let position = Position::new(4, 3);
+        let favourite = |position: &Position| {
+            let mut priors = vec![0.0; position.size()];
+            let options = position.options();
+            priors[*options.last().expect("an option")] = 1.0;
+            priors
+        };
+        let mut strategy = MonteCarlo::strategy()
+            .simulations(50)
+            .exploration(100.0)
+            .with_prior(favourite)
+            .seeded(37)
+            .build();
+
+        strategy.play(&position);
+        let report = strategy.report().expect("a report");
+
+        assert_eq!(report.principal_variation.first().copied(), Some(3));
+    }
+
+    #[test]
+    fn short_priors_are_padded() {
+        let position = Position::new(4, 3);
+        let mut strategy = MonteCarlo::strategy()
+            .simulations(50)
+            .with_prior(|_: &Position| vec![1.0])
+            .seeded(37)
+            .build();
+
+        assert!(strategy.play(&position).is_some());
+    }
+
+    #[test]
+    fn valued_leaves_select_winning_bowl() {
+        let position = Position::from([1, 3, 0, 5, 0, 1]);
+        let mut strategy = MonteCarlo::strategy()
+            .simulations(200)
+            .valued()
+            .with_prior(uniform())
+            .seeded(37)
+            .build();
 
-        assert_eq!(value, Value::Actual(5));
+        assert_eq!(strategy.play(&position), Some(0));
+    }
+
+    #[test]
+    fn valued_leaves_are_not_played_out() {
+        let position = Position::new(6, 4);
+        let mut strategy = MonteCarlo::strategy()
+            .simulations(10)
+            .valued()
+            .seeded(37)
+            .build();
+
+        strategy.play(&position);
+        let report = strategy.report().expect("a report");
+
+        assert!(report.nodes <= 10 * 3);
+    }
+
+    #[test]
+    fn uniform_prior_selects_winning_bowl() {
+        let position = Position::from([1, 3, 0, 5, 0, 1]);
+        let mut strategy = MonteCarlo::strategy()
+            .with_prior(uniform())
+            .seeded(37)
+            .build();
+
+        assert_eq!(strategy.play(&position), Some(0));
+    }
+
+    #[test]
+    fn playouts_are_scored_for_the_mover() {
+        let win = Playout::from_value(Player::Blue, Value::Actual(3));
+
+        assert_eq!(win, Playout::Loss);
+        assert_eq!(win.reward(Player::Blue), 1.0);
+        assert_eq!(win.reward(Player::Red), 0.0);
+    }
+
+    #[test]
+    fn estimated_draws_are_draws() {
+        assert_eq!(
+            Playout::from_value(Player::Red, Value::Estimate(0)),
+            Playout::Draw
+        );
+        assert_eq!(
+            Playout::from_value(Player::Red, Value::Actual(0)),
+            Playout::Draw
+        );
     }
 }
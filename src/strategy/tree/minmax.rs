@@ -3,55 +3,229 @@
 //! The [minmax strategy](https://en.wikipedia.org/wiki/Minimax) is a
 //!
 //! > decision rule used in artificial intelligence, decision theory, game theory, statistics and philosophy for minimizing the possible loss for a worst case (maximum loss) scenario. When dealing with gains, it is referred to as "maximin"—to maximize the minimum gain. Originally formulated for two-player zero-sum game theory, covering both the cases where players take alternate moves and those where they make simultaneous moves, it has also been extended to more complex games and to general decision-making in the presence of uncertainty.
+//!
+//! Without pruning, it visits every position up to its search depth. That makes it slow, but also a reliable reference
+//! for other searches.
+//!
+//! The way to create a `MinMax` strategy is
+//!
+//! ```
+//! use mancala::strategy::tree::{MinMax, Depth};
+//! use mancala::strategy::heuristic::delta;
+//!
+//! let strategy = MinMax::strategy().limited_to(Depth::Limit(5)).with_heuristic(delta()).build();
+//! ```
 
+use super::{Depth, DepthLimitedSearch, NODES_BETWEEN_DEADLINE_CHECKS};
 use crate::{
     game::{Bowl, Position},
-    strategy::{heuristic::Value, Strategy},
+    strategy::{
+        analysis::{analyse_with, Analyse, Analysis},
+        heuristic::{delta, Delta, Heuristic, Value},
+        SearchReport, Strategy,
+    },
 };
 use std::collections::HashMap;
 use std::fmt::{self, Display, Formatter};
+use std::time::Instant;
+
+/// Build MinMax strategy instances
+pub struct MinMaxBuilder<H>
+where
+    H: Heuristic + Sized,
+{
+    search_depth: Depth,
+    heuristic: H,
+}
+
+impl<H> MinMaxBuilder<H>
+where
+    H: Heuristic + Sized,
+{
+    /// Build a MinMax strategy
+    pub fn build(self) -> MinMax<H> {
+        MinMax {
+            search_depth: self.search_depth,
+            heuristic: self.heuristic,
+            analyzer: Analyzer::new(),
+            report: SearchReport::new(),
+        }
+    }
+
+    /// limited to a certain search depth
+    pub fn limited_to(mut self, search_depth: Depth) -> Self {
+        self.search_depth = search_depth;
+        self
+    }
+
+    /// with a certain heuristic
+    pub fn with_heuristic<H_>(self, heuristic: H_) -> MinMaxBuilder<H_>
+    where
+        H_: Heuristic + Sized,
+    {
+        MinMaxBuilder {
+            search_depth: self.search_depth,
+            heuristic,
+        }
+    }
+}
 
 /// Pick the option that maximizes the minimum win.
-pub struct MinMax {
+pub struct MinMax<H = Delta>
+where
+    H: Heuristic + Sized,
+{
+    search_depth: Depth,
+    heuristic: H,
     /// An Analyzer that keeps track of various statistics.
     pub analyzer: Analyzer,
+    report: SearchReport,
 }
 
-impl MinMax {
+impl MinMax<Delta> {
+    /// Create a default MinMaxBuilder
+    ///
+    /// It has an unlimited search depth and the Delta heuristic.
+    pub fn strategy() -> MinMaxBuilder<Delta> {
+        MinMaxBuilder {
+            search_depth: Depth::Infinite,
+            heuristic: delta(),
+        }
+    }
+
     /// Create a default MinMax strategy
     pub fn new() -> Self {
-        Self {
-            analyzer: Analyzer::new(),
-        }
+        Self::strategy().build()
     }
 }
 
-impl Default for MinMax {
+impl Default for MinMax<Delta> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl Strategy for MinMax {
+impl<H> Strategy for MinMax<H>
+where
+    H: Heuristic + Sized,
+{
     fn play(&mut self, position: &Position) -> Option<Bowl> {
-        let (bowl, _) = minmax(&mut self.analyzer, position);
+        let search_depth = self.search_depth;
+        let (bowl, _) = self.search(position, &search_depth);
         bowl
     }
+
+    fn report(&self) -> Option<&SearchReport> {
+        Some(&self.report)
+    }
+}
+
+impl<H> DepthLimitedSearch<Position, (Option<Bowl>, Value)> for MinMax<H>
+where
+    H: Heuristic + Sized,
+{
+    fn search(&mut self, position: &Position, search_depth: &Depth) -> (Option<Bowl>, Value) {
+        self.search_with(position, search_depth, None)
+            .expect("search without deadline to complete")
+    }
+
+    fn search_until(
+        &mut self,
+        position: &Position,
+        search_depth: &Depth,
+        deadline: Instant,
+    ) -> Option<(Option<Bowl>, Value)> {
+        self.search_with(position, search_depth, Some(deadline))
+    }
+
+    fn exhausted(&self) -> bool {
+        !self.analyzer.aborted && !self.analyzer.cut_by_depth
+    }
+
+    fn search_report(&self) -> Option<&SearchReport> {
+        Some(&self.report)
+    }
+}
+
+impl<H> MinMax<H>
+where
+    H: Heuristic + Sized,
+{
+    fn search_with(
+        &mut self,
+        position: &Position,
+        search_depth: &Depth,
+        deadline: Option<Instant>,
+    ) -> Option<(Option<Bowl>, Value)> {
+        let start = Instant::now();
+        let nodes = self.analyzer.nodes();
+        self.analyzer.cut_by_depth = false;
+        self.analyzer.aborted = false;
+        self.analyzer.deadline = deadline;
+        let mut line = vec![];
+        let result = minmax(
+            &mut self.analyzer,
+            position,
+            search_depth,
+            &self.heuristic,
+            &mut line,
+        );
+        self.report = SearchReport {
+            nodes: self.analyzer.nodes() - nodes,
+            elapsed: start.elapsed(),
+            ..SearchReport::new()
+        };
+        if self.analyzer.aborted {
+            None
+        } else {
+            self.report.value = Some(result.1);
+            self.report.principal_variation = line;
+            Some(result)
+        }
+    }
 }
 
-fn minmax(analyzer: &mut Analyzer, position: &Position) -> (Option<Bowl>, Value) {
+impl<H> Analyse for MinMax<H>
+where
+    H: Heuristic + Sized,
+{
+    fn analyse(&mut self, position: &Position, search_depth: &Depth, lines: usize) -> Analysis {
+        analyse_with(self, position, search_depth, lines)
+    }
+}
+
+/// Search `position`, filling `line` with the best line of play.
+fn minmax(
+    analyzer: &mut Analyzer,
+    position: &Position,
+    search_depth: &Depth,
+    heuristic: &dyn Heuristic,
+    line: &mut Vec<Bowl>,
+) -> (Option<Bowl>, Value) {
     analyzer.count();
-    if position.finished() {
+    if analyzer.out_of_time() {
+        (None, Value::NegativeInfinity)
+    } else if position.finished() {
         (
             None,
             Value::Actual(position.score().expect("finished game to have a score")),
         )
+    } else if search_depth.is_zero() {
+        analyzer.cut_by_depth = true;
+        (None, heuristic.evaluate(position))
     } else {
         let (mut best_bowl, mut best_value) = (None, Value::NegativeInfinity);
         for bowl in position.options() {
             let candidate_position = position.play(bowl).expect("option to be playable");
             analyzer.increment_depth();
-            let (_, mut value) = minmax(analyzer, &candidate_position);
+            let mut candidate_line = vec![];
+            let (_, mut value) = minmax(
+                analyzer,
+                &candidate_position,
+                &search_depth.decrement(),
+                heuristic,
+                &mut candidate_line,
+            );
             analyzer.decrement_depth();
             if candidate_position.turn() != position.turn() {
                 value = value.opposite();
@@ -59,6 +233,12 @@ fn minmax(analyzer: &mut Analyzer, position: &Position) -> (Option<Bowl>, Value)
             if value > best_value {
                 best_bowl = Some(bowl);
                 best_value = value;
+                line.clear();
+                line.push(bowl);
+                line.append(&mut candidate_line);
+            }
+            if analyzer.aborted {
+                return (None, Value::NegativeInfinity);
             }
         }
         (best_bowl, best_value)
@@ -69,6 +249,10 @@ fn minmax(analyzer: &mut Analyzer, position: &Position) -> (Option<Bowl>, Value)
 pub struct Analyzer {
     depth_counter: HashMap<u64, u64>,
     current_depth: u64,
+    cut_by_depth: bool,
+    clock: usize,
+    deadline: Option<Instant>,
+    aborted: bool,
 }
 
 impl Analyzer {
@@ -77,9 +261,17 @@ impl Analyzer {
         Self {
             depth_counter: HashMap::new(),
             current_depth: 0,
+            cut_by_depth: false,
+            clock: 0,
+            deadline: None,
+            aborted: false,
         }
     }
 
+    fn nodes(&self) -> u64 {
+        self.depth_counter.values().sum()
+    }
+
     fn count(&mut self) {
         let count = if self.depth_counter.contains_key(&self.current_depth) {
             self.depth_counter.get(&self.current_depth).unwrap()
@@ -89,6 +281,18 @@ impl Analyzer {
         self.depth_counter.insert(self.current_depth, count + 1);
     }
 
+    fn out_of_time(&mut self) -> bool {
+        if !self.aborted {
+            self.clock = (self.clock + 1) % NODES_BETWEEN_DEADLINE_CHECKS;
+            if self.clock == 0 {
+                if let Some(deadline) = self.deadline {
+                    self.aborted = Instant::now() >= deadline;
+                }
+            }
+        }
+        self.aborted
+    }
+
     fn increment_depth(&mut self) {
         self.current_depth += 1;
     }
@@ -107,8 +311,7 @@ impl Default for Analyzer {
 impl Display for Analyzer {
     fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
         let max_depth = self.depth_counter.keys().max().unwrap();
-        let node_count: u64 = self.depth_counter.values().sum();
-        write!(formatter, "nodes: {} depth: {}", node_count, max_depth)
+        write!(formatter, "nodes: {} depth: {}", self.nodes(), max_depth)
     }
 }
 
@@ -116,13 +319,20 @@ impl Display for Analyzer {
 mod tests {
     use super::*;
     use crate::game::Position;
+    use std::time::Duration;
 
     #[test]
     fn finished_games_are_scored() {
         let position = Position::from((5, 0, [0, 0, 2, 2]));
         let mut analyzer = Analyzer::new();
 
-        let (bowl, value) = minmax(&mut analyzer, &position);
+        let (bowl, value) = minmax(
+            &mut analyzer,
+            &position,
+            &Depth::Infinite,
+            &delta(),
+            &mut vec![],
+        );
 
         assert_eq!(value, Value::Actual(1));
         assert_eq!(bowl, None);
@@ -133,7 +343,13 @@ mod tests {
         let position = Position::from([1, 0, 1, 0]);
         let mut analyzer = Analyzer::new();
 
-        let result = minmax(&mut analyzer, &position);
+        let result = minmax(
+            &mut analyzer,
+            &position,
+            &Depth::Infinite,
+            &delta(),
+            &mut vec![],
+        );
 
         assert_eq!(result, (Some(0), Value::Actual(2)));
     }
@@ -143,8 +359,77 @@ mod tests {
         let position = Position::from([1, 2, 1, 0, 2, 1]);
         let mut analyzer = Analyzer::new();
 
-        let (_, value) = minmax(&mut analyzer, &position);
+        let (_, value) = minmax(
+            &mut analyzer,
+            &position,
+            &Depth::Infinite,
+            &delta(),
+            &mut vec![],
+        );
 
         assert_eq!(value, Value::Actual(5));
     }
+
+    #[test]
+    fn report_contains_principal_variation() {
+        let position = Position::from([1, 2, 1, 0, 2, 1]);
+        let mut strategy = MinMax::new();
+
+        let bowl = strategy.play(&position);
+        let report = strategy.report().expect("a report");
+
+        assert_eq!(report.value, Some(Value::Actual(5)));
+        assert_eq!(report.principal_variation.first().copied(), bowl);
+        assert_eq!(report.nodes, strategy.analyzer.nodes());
+        let mut end = position;
+        for bowl in &report.principal_variation {
+            end.make(*bowl).expect("principal variation to be playable");
+        }
+        assert!(end.finished());
+    }
+
+    #[test]
+    fn analysis_values_every_option() {
+        let position = Position::from([1, 2, 1, 0, 2, 1]);
+        let mut strategy = MinMax::new();
+
+        let analysis = strategy.analyse(&position, &Depth::Infinite, 3);
+        let values: Vec<Value> = analysis.lines().iter().map(|line| line.value).collect();
+
+        assert_eq!(values.len(), position.options().len());
+        assert_eq!(values[0], Value::Actual(5));
+        assert!(values.windows(2).all(|pair| pair[0] >= pair[1]));
+    }
+
+    #[test]
+    fn limited_search_uses_heuristic() {
+        let position = Position::new(6, 4);
+        let mut strategy = MinMax::strategy()
+            .limited_to(Depth::Limit(1))
+            .with_heuristic(|_: &Position| Value::Actual(7))
+            .build();
+
+        let bowl = strategy.play(&position);
+        let value = strategy.report().and_then(|report| report.value);
+
+        assert_eq!(bowl, Some(2));
+        assert_eq!(value, Some(Value::Actual(7)));
+    }
+
+    #[test]
+    fn search_gives_up_at_the_deadline() {
+        let position = Position::new(6, 4);
+        let mut strategy = MinMax::new();
+
+        let start = Instant::now();
+        let result = strategy.search_until(
+            &position,
+            &Depth::Infinite,
+            start + Duration::from_millis(50),
+        );
+
+        assert_eq!(result, None);
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(!strategy.exhausted());
+    }
 }
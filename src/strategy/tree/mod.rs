@@ -4,11 +4,19 @@ pub mod alphabeta;
 pub mod ids;
 pub mod mcts;
 pub mod minmax;
+pub mod ordering;
+pub mod table;
 
 pub use self::alphabeta::AlphaBeta;
+pub use self::ids::IterativeDeepeningSearch;
 pub use self::mcts::MonteCarlo;
 pub use self::minmax::MinMax;
+use crate::{game::Bowl, strategy::SearchReport};
 use std::cmp::PartialOrd;
+use std::time::Instant;
+
+/// The number of nodes searched between looking at the clock.
+const NODES_BETWEEN_DEADLINE_CHECKS: usize = 1024;
 
 /// Determine the search depth of tree algorithms
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
@@ -86,6 +94,31 @@ impl Iterator for DepthIterator {
 pub trait DepthLimitedSearch<I, O> {
     /// Search up to `search_depth` levels
     fn search(&mut self, start: &I, search_depth: &Depth) -> O;
+
+    /// Search up to `search_depth` levels, giving up when `deadline` has passed.
+    ///
+    /// Returns `None` when the search gave up. By default the search is not interrupted and ignores the deadline.
+    fn search_until(&mut self, start: &I, search_depth: &Depth, _deadline: Instant) -> Option<O> {
+        Some(self.search(start, search_depth))
+    }
+
+    /// Hint that `bowl` should be searched first in the next search.
+    ///
+    /// Searches that order their plays can use this to try the best play of an earlier search first. By default the
+    /// hint is ignored.
+    fn prefer(&mut self, _bowl: Bowl) {}
+
+    /// Determine if the last completed search reached the end of every line it followed.
+    ///
+    /// A deeper search would then find the same. By default searches are assumed to be cut off by their depth.
+    fn exhausted(&self) -> bool {
+        false
+    }
+
+    /// Report on the last search, whether it completed or gave up.
+    fn search_report(&self) -> Option<&SearchReport> {
+        None
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,173 @@
+//! Orderings of plays for tree searches.
+//!
+//! Alpha-beta pruning cuts off more of the tree when the best play is searched first. An ordering guesses which plays
+//! are most promising, before searching them.
+//!
+//! * `InOrder` searches the bowls in index order.
+//! * `Prioritized` searches plays that earn an extra turn first, followed by plays that capture stones. The remaining
+//!   plays are ordered by the [killer heuristic](https://www.chessprogramming.org/Killer_Heuristic) and the
+//!   [history heuristic](https://www.chessprogramming.org/History_Heuristic).
+//!
+//! Plays of the principal variation, remembered in the transposition table or by an earlier iteration, are searched
+//! before any of these.
+
+use super::Depth;
+use crate::{
+    game::{Bowl, Player, Position, MAX_BOWLS},
+    strategy::heuristic::captured,
+};
+
+/// The number of killer plays remembered per ply.
+const KILLERS: usize = 2;
+
+/// A way to order the plays of a position.
+pub trait PlayOrdering {
+    /// Order `options` of `position`, that is `ply` plays from the root, with the most promising play first.
+    fn order(&mut self, position: &Position, ply: usize, options: &mut Vec<Bowl>);
+
+    /// Learn that playing `bowl` in `position` caused a cutoff, with `search_depth` levels left to search.
+    fn cutoff(&mut self, _position: &Position, _ply: usize, _bowl: Bowl, _search_depth: &Depth) {}
+
+    /// Prepare for a new search.
+    fn start(&mut self) {}
+}
+
+/// Search the bowls in index order.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct InOrder {}
+
+impl PlayOrdering for InOrder {
+    fn order(&mut self, _position: &Position, _ply: usize, _options: &mut Vec<Bowl>) {}
+}
+
+/// Search extra turns first, captures next, followed by killer plays and plays with a good history.
+#[derive(Debug, Clone)]
+pub struct Prioritized {
+    killers: Vec<[Option<Bowl>; KILLERS]>,
+    history: [[u64; MAX_BOWLS]; 2],
+}
+
+impl Prioritized {
+    /// Create a prioritized ordering without any history.
+    pub fn new() -> Self {
+        Prioritized {
+            killers: vec![],
+            history: [[0; MAX_BOWLS]; 2],
+        }
+    }
+
+    fn is_killer(&self, ply: usize, bowl: Bowl) -> bool {
+        self.killers
+            .get(ply)
+            .map(|killers| killers.contains(&Some(bowl)))
+            .unwrap_or(false)
+    }
+
+    fn history(&self, player: Player, bowl: Bowl) -> u64 {
+        self.history[side(player)][bowl]
+    }
+}
+
+impl Default for Prioritized {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PlayOrdering for Prioritized {
+    fn order(&mut self, position: &Position, ply: usize, options: &mut Vec<Bowl>) {
+        let player = position.turn();
+        options.sort_by_cached_key(|bowl| {
+            let after = position.play(*bowl).expect("option to be playable");
+            let extra_turn = after.turn() == player && !after.finished();
+            let capture = captured(position, *bowl, &after) > 0;
+            (
+                !extra_turn,
+                !capture,
+                !self.is_killer(ply, *bowl),
+                u64::MAX - self.history(player, *bowl),
+            )
+        });
+    }
+
+    fn cutoff(&mut self, position: &Position, ply: usize, bowl: Bowl, search_depth: &Depth) {
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, [None; KILLERS]);
+        }
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(bowl) {
+            killers.rotate_right(1);
+            killers[0] = Some(bowl);
+        }
+        let bonus = match search_depth {
+            Depth::Infinite => 1,
+            Depth::Limit(depth) => (*depth as u64) * (*depth as u64),
+        };
+        let entry = &mut self.history[side(position.turn())][bowl];
+        *entry = entry.saturating_add(bonus);
+    }
+
+    fn start(&mut self) {
+        self.killers.clear();
+        for side in self.history.iter_mut() {
+            for entry in side.iter_mut() {
+                *entry /= 2;
+            }
+        }
+    }
+}
+
+fn side(player: Player) -> usize {
+    match player {
+        Player::Red => 0,
+        Player::Blue => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extra_turns_come_first() {
+        let position = Position::from([0, 0, 1, 1, 4, 4, 4, 4]);
+        let mut options = position.options();
+
+        Prioritized::new().order(&position, 0, &mut options);
+
+        assert_eq!(options, vec![3, 2]);
+    }
+
+    #[test]
+    fn captures_come_before_quiet_plays() {
+        let position = Position::from([1, 0, 0, 3, 0, 0, 0, 0, 2, 0]);
+        let mut options = position.options();
+
+        Prioritized::new().order(&position, 0, &mut options);
+
+        assert_eq!(options, vec![0, 3]);
+    }
+
+    #[test]
+    fn killers_come_before_other_quiet_plays() {
+        let position = Position::new(6, 4);
+        let mut ordering = Prioritized::new();
+        let mut options = position.options();
+
+        ordering.cutoff(&position, 3, 4, &Depth::Limit(1));
+        ordering.order(&position, 3, &mut options);
+
+        assert_eq!(options[0], 2);
+        assert_eq!(options[1], 4);
+    }
+
+    #[test]
+    fn in_order_leaves_options_alone() {
+        let position = Position::new(6, 4);
+        let mut options = position.options();
+
+        InOrder {}.order(&position, 0, &mut options);
+
+        assert_eq!(options, vec![0, 1, 2, 3, 4, 5]);
+    }
+}
@@ -0,0 +1,264 @@
+//! A transposition table for tree searches.
+//!
+//! Different orders of play often lead to the same position. Extra turns make this especially common in mancala. A
+//! [transposition table](https://en.wikipedia.org/wiki/Transposition_table) remembers what a search learned about a
+//! position, so that the next time the position is reached the result can be reused.
+//!
+//! The table has a fixed number of slots, determined by the memory it is allowed to use. A position is stored in the
+//! slot its hash key points to. When that slot is already taken, the replacement policy decides which entry to keep:
+//!
+//! 1. Entries from an earlier search are always replaced.
+//! 2. Entries for the same position are always replaced.
+//! 3. Otherwise the entry that was searched deepest is kept.
+
+use super::Depth;
+use crate::{
+    game::{Bowl, Position},
+    strategy::heuristic::Value,
+};
+use std::mem::size_of;
+
+/// How the stored value relates to the actual value of a position.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Bound {
+    /// The stored value is the actual value.
+    Exact,
+    /// The actual value is at least the stored value.
+    Lower,
+    /// The actual value is at most the stored value.
+    Upper,
+}
+
+/// What a search learned about a position.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Entry {
+    key: u64,
+    generation: u32,
+    /// The depth the position was searched to.
+    pub depth: Depth,
+    /// The value of the position, for the player to move.
+    pub value: Value,
+    /// How `value` relates to the actual value.
+    pub bound: Bound,
+    /// The best bowl found, if any.
+    pub bowl: Option<Bowl>,
+}
+
+impl Entry {
+    /// Determine if this entry was searched at least as deep as `depth`.
+    pub fn covers(&self, depth: &Depth) -> bool {
+        match (self.depth, depth) {
+            (Depth::Infinite, _) => true,
+            (Depth::Limit(_), Depth::Infinite) => false,
+            (Depth::Limit(stored), Depth::Limit(wanted)) => stored >= *wanted,
+        }
+    }
+}
+
+/// A bounded table of entries, indexed by the hash key of positions.
+pub struct TranspositionTable {
+    slots: Vec<Option<Entry>>,
+    generation: u32,
+}
+
+impl TranspositionTable {
+    /// Create a table that uses at most `bytes` of memory for its entries.
+    ///
+    /// A table too small to hold a single entry stores nothing.
+    pub fn with_size(bytes: usize) -> Self {
+        let capacity = bytes / size_of::<Option<Entry>>();
+        TranspositionTable {
+            slots: vec![None; capacity],
+            generation: 0,
+        }
+    }
+
+    /// The number of entries this table can hold.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Mark the start of a new search.
+    ///
+    /// Entries from earlier searches remain available, but are replaced first.
+    pub fn next_generation(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    /// Look up the entry for a position.
+    pub fn probe(&self, position: &Position) -> Option<Entry> {
+        let key = position.hash_key();
+        self.slot(key)
+            .and_then(|index| self.slots[index])
+            .filter(|entry| entry.key == key)
+    }
+
+    /// Store what a search learned about a position, subject to the replacement policy.
+    pub fn store(
+        &mut self,
+        position: &Position,
+        depth: Depth,
+        value: Value,
+        bound: Bound,
+        bowl: Option<Bowl>,
+    ) {
+        let key = position.hash_key();
+        if let Some(index) = self.slot(key) {
+            let entry = Entry {
+                key,
+                generation: self.generation,
+                depth,
+                value,
+                bound,
+                bowl,
+            };
+            let replace = match self.slots[index] {
+                None => true,
+                Some(stored) => {
+                    stored.generation != self.generation
+                        || stored.key == key
+                        || entry.covers(&stored.depth)
+                }
+            };
+            if replace {
+                self.slots[index] = Some(entry);
+            }
+        }
+    }
+
+    fn slot(&self, key: u64) -> Option<usize> {
+        if self.slots.is_empty() {
+            None
+        } else {
+            Some((key % self.slots.len() as u64) as usize)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Oware;
+
+    #[test]
+    fn stored_entries_can_be_probed() {
+        let position = Position::new(3, 3);
+        let mut table = TranspositionTable::with_size(1 << 10);
+
+        table.store(
+            &position,
+            Depth::Limit(2),
+            Value::Actual(3),
+            Bound::Exact,
+            Some(1),
+        );
+        let entry = table.probe(&position).expect("an entry");
+
+        assert_eq!(entry.value, Value::Actual(3));
+        assert_eq!(entry.bound, Bound::Exact);
+        assert_eq!(entry.bowl, Some(1));
+    }
+
+    #[test]
+    fn entries_are_not_shared_between_variants() {
+        let position = Position::new(3, 3);
+        let oware = position.with_rules(Oware::new());
+        let mut table = TranspositionTable::with_size(1 << 10);
+
+        table.store(
+            &position,
+            Depth::Infinite,
+            Value::Actual(3),
+            Bound::Exact,
+            Some(1),
+        );
+
+        assert_eq!(table.probe(&oware), None);
+    }
+
+    #[test]
+    fn tables_without_room_store_nothing() {
+        let position = Position::new(3, 3);
+        let mut table = TranspositionTable::with_size(0);
+
+        table.store(
+            &position,
+            Depth::Limit(2),
+            Value::Actual(3),
+            Bound::Exact,
+            Some(1),
+        );
+
+        assert_eq!(table.capacity(), 0);
+        assert_eq!(table.probe(&position), None);
+    }
+
+    #[test]
+    fn deeper_entries_are_kept() {
+        let position = Position::new(3, 3);
+        let other = position.play(0).expect("a playable bowl");
+        let mut table = TranspositionTable::with_size(size_of::<Option<Entry>>());
+
+        table.store(
+            &position,
+            Depth::Limit(4),
+            Value::Actual(3),
+            Bound::Exact,
+            Some(1),
+        );
+        table.store(
+            &other,
+            Depth::Limit(2),
+            Value::Actual(1),
+            Bound::Exact,
+            Some(2),
+        );
+
+        assert!(table.probe(&position).is_some());
+        assert_eq!(table.probe(&other), None);
+    }
+
+    #[test]
+    fn entries_of_earlier_searches_are_replaced() {
+        let position = Position::new(3, 3);
+        let other = position.play(0).expect("a playable bowl");
+        let mut table = TranspositionTable::with_size(size_of::<Option<Entry>>());
+
+        table.store(
+            &position,
+            Depth::Limit(4),
+            Value::Actual(3),
+            Bound::Exact,
+            Some(1),
+        );
+        table.next_generation();
+        table.store(
+            &other,
+            Depth::Limit(2),
+            Value::Actual(1),
+            Bound::Exact,
+            Some(2),
+        );
+
+        assert_eq!(table.probe(&position), None);
+        assert!(table.probe(&other).is_some());
+    }
+
+    #[test]
+    fn infinite_depth_covers_every_depth() {
+        let position = Position::new(3, 3);
+        let mut table = TranspositionTable::with_size(1 << 10);
+
+        table.store(
+            &position,
+            Depth::Infinite,
+            Value::Actual(3),
+            Bound::Exact,
+            Some(1),
+        );
+        let entry = table.probe(&position).expect("an entry");
+
+        assert!(entry.covers(&Depth::Limit(100)));
+        assert!(entry.covers(&Depth::Infinite));
+    }
+}
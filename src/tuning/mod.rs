@@ -0,0 +1,444 @@
+//! Tune the weights of heuristics by self-play.
+//!
+//! Guessing the weights of a `Weighted` heuristic is hard. Instead, the weights can be learned by letting `AlphaBeta`
+//! strategies with slightly different weights play bouts against each other, and moving the weights in the direction
+//! of the winner.
+//!
+//! `Spsa` uses [simultaneous perturbation stochastic approximation](https://en.wikipedia.org/wiki/Simultaneous_perturbation_stochastic_approximation).
+//! Every iteration perturbs all weights at once, plays pairs of games between the weights perturbed up and the weights
+//! perturbed down, and estimates the gradient from the result. Each pair of games starts from the same random
+//! opening, with both sides playing `Red` once.
+//!
+//! ```
+//! use mancala::game::GameBuilder;
+//! use mancala::strategy::heuristic::{delta, mobility, Weighted};
+//! use mancala::strategy::tree::Depth;
+//! use mancala::strategy::AlphaBeta;
+//! use mancala::tuning::{Spsa, Weights};
+//!
+//! let features = || Weighted::new().with(1.0, delta()).with(0.5, mobility());
+//! let mut spsa = Spsa::tuning(features)
+//!     .iterations(2)
+//!     .games(1)
+//!     .limited_to(Depth::Limit(1))
+//!     .on(GameBuilder::new().bowls(3).stones(2))
+//!     .seeded(7)
+//!     .build()
+//!     .expect("a playable game");
+//! let tuning = spsa.tune();
+//!
+//! let written = tuning.weights().to_string();
+//! let weights: Weights = written.parse().expect("weights to be read back");
+//! let mut heuristic = features();
+//! heuristic.set_weights(weights.values());
+//! let strategy = AlphaBeta::strategy().with_heuristic(heuristic).build();
+//! ```
+
+mod weights;
+
+pub use self::weights::{BadWeights, Weights};
+
+use crate::{
+    bout::Bout,
+    game::{BadSetup, Game, GameBuilder, Outcome},
+    strategy::{heuristic::Weighted, tree::Depth, AlphaBeta, Strategy},
+};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::fmt::{self, Display, Formatter};
+
+/// The exponent with which the learning rate decays.
+const ALPHA: f64 = 0.602;
+/// The exponent with which the perturbation decays.
+const GAMMA: f64 = 0.101;
+
+/// Build Spsa tuners
+pub struct SpsaBuilder<F>
+where
+    F: Fn() -> Weighted,
+{
+    features: F,
+    start: Option<Weights>,
+    iterations: usize,
+    games: usize,
+    search_depth: Depth,
+    learning_rate: f64,
+    perturbation: f64,
+    openings: usize,
+    setup: GameBuilder,
+    seed: Option<u64>,
+}
+
+impl<F> SpsaBuilder<F>
+where
+    F: Fn() -> Weighted,
+{
+    /// Build a Spsa tuner
+    ///
+    /// Fails when the setup does not create a playable game.
+    pub fn build(self) -> Result<Spsa<F>, BadSetup> {
+        self.setup.build()?;
+        let mut weights = (self.features)().weights();
+        if let Some(start) = self.start {
+            for (weight, start) in weights.iter_mut().zip(start.values()) {
+                *weight = *start;
+            }
+        }
+        let rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Ok(Spsa {
+            features: self.features,
+            weights,
+            iteration: 0,
+            iterations: self.iterations,
+            games: self.games,
+            search_depth: self.search_depth,
+            learning_rate: self.learning_rate,
+            perturbation: self.perturbation,
+            openings: self.openings,
+            setup: self.setup,
+            rng,
+        })
+    }
+
+    /// starting from certain weights instead of the weights of the features
+    ///
+    /// Like `Weighted::set_weights`, superfluous weights are ignored.
+    pub fn starting_at(mut self, weights: Weights) -> Self {
+        self.start = Some(weights);
+        self
+    }
+
+    /// running a certain number of iterations
+    pub fn iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// playing a certain number of pairs of games per iteration
+    pub fn games(mut self, games: usize) -> Self {
+        self.games = games;
+        self
+    }
+
+    /// with strategies limited to a certain search depth
+    pub fn limited_to(mut self, search_depth: Depth) -> Self {
+        self.search_depth = search_depth;
+        self
+    }
+
+    /// with a certain initial learning rate
+    pub fn learning_rate(mut self, learning_rate: f64) -> Self {
+        self.learning_rate = learning_rate;
+        self
+    }
+
+    /// with a certain initial size of the perturbations of the weights
+    pub fn perturbation(mut self, perturbation: f64) -> Self {
+        self.perturbation = perturbation;
+        self
+    }
+
+    /// starting games after a certain number of random plays
+    pub fn openings(mut self, openings: usize) -> Self {
+        self.openings = openings;
+        self
+    }
+
+    /// playing games created by a certain setup
+    pub fn on(mut self, setup: GameBuilder) -> Self {
+        self.setup = setup;
+        self
+    }
+
+    /// with a seed, so that tuning can be repeated
+    pub fn seeded(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+}
+
+/// Tune the weights of a `Weighted` heuristic with simultaneous perturbation stochastic approximation.
+pub struct Spsa<F>
+where
+    F: Fn() -> Weighted,
+{
+    features: F,
+    weights: Vec<f64>,
+    iteration: usize,
+    iterations: usize,
+    games: usize,
+    search_depth: Depth,
+    learning_rate: f64,
+    perturbation: f64,
+    openings: usize,
+    setup: GameBuilder,
+    rng: StdRng,
+}
+
+impl<F> Spsa<F>
+where
+    F: Fn() -> Weighted,
+{
+    /// Create a default SpsaBuilder for the heuristics created by `features`
+    ///
+    /// It starts at the weights of the features and runs 100 iterations of 8 pairs of games, with a search depth of 3,
+    /// a learning rate of 0.05 and a perturbation of 0.25. Games are the default games of a `GameBuilder`, starting
+    /// after 2 random plays.
+    pub fn tuning(features: F) -> SpsaBuilder<F> {
+        SpsaBuilder {
+            features,
+            start: None,
+            iterations: 100,
+            games: 8,
+            search_depth: Depth::Limit(3),
+            learning_rate: 0.05,
+            perturbation: 0.25,
+            openings: 2,
+            setup: GameBuilder::new(),
+            seed: None,
+        }
+    }
+
+    /// The current weights.
+    pub fn weights(&self) -> Weights {
+        Weights::new(self.weights.clone())
+    }
+
+    /// Run all iterations, recording each of them.
+    pub fn tune(&mut self) -> Tuning {
+        let iterations = (0..self.iterations).map(|_| self.step()).collect();
+        Tuning {
+            iterations,
+            weights: self.weights(),
+        }
+    }
+
+    /// Run a single iteration.
+    pub fn step(&mut self) -> Iteration {
+        let k = self.iteration as f64;
+        let stability = self.iterations as f64 / 10.0;
+        let learning_rate = self.learning_rate / (k + 1.0 + stability).powf(ALPHA);
+        let perturbation = self.perturbation / (k + 1.0).powf(GAMMA);
+
+        let rng = &mut self.rng;
+        let directions: Vec<f64> = self
+            .weights
+            .iter()
+            .map(|_| if rng.gen() { 1.0 } else { -1.0 })
+            .collect();
+        let perturbed = |sign: f64| -> Vec<f64> {
+            self.weights
+                .iter()
+                .zip(&directions)
+                .map(|(weight, direction)| weight + sign * perturbation * direction)
+                .collect()
+        };
+        let (up, down) = (perturbed(1.0), perturbed(-1.0));
+        let score = self.score(&up, &down);
+
+        for (weight, direction) in self.weights.iter_mut().zip(&directions) {
+            *weight += learning_rate * score / (2.0 * perturbation * direction);
+        }
+        self.iteration += 1;
+        Iteration {
+            iteration: self.iteration,
+            score,
+            weights: self.weights(),
+        }
+    }
+
+    /// The average result of the games between `up` and `down`, from the perspective of `up`.
+    fn score(&mut self, up: &[f64], down: &[f64]) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        let mut up = self.strategy(up);
+        let mut down = self.strategy(down);
+        let mut total = 0.0;
+        for _ in 0..self.games {
+            let opening = self.opening();
+            total += result(play(&mut up, &mut down, opening.clone()), 1.0);
+            total += result(play(&mut down, &mut up, opening), -1.0);
+        }
+        total / (2 * self.games) as f64
+    }
+
+    fn strategy(&self, weights: &[f64]) -> AlphaBeta<Weighted> {
+        let mut heuristic = (self.features)();
+        heuristic.set_weights(weights);
+        AlphaBeta::strategy()
+            .limited_to(self.search_depth)
+            .with_heuristic(heuristic)
+            .build()
+    }
+
+    /// A game after random plays, none of which finish the game.
+    fn opening(&mut self) -> Game {
+        let mut game = self.setup.build().expect("a playable game");
+        for _ in 0..self.openings {
+            let candidates: Vec<_> = game
+                .options()
+                .into_iter()
+                .filter(|bowl| {
+                    !game
+                        .current
+                        .play(*bowl)
+                        .expect("option to be playable")
+                        .finished()
+                })
+                .collect();
+            match candidates.choose(&mut self.rng) {
+                Some(bowl) => game.play(*bowl).expect("option to be playable"),
+                None => break,
+            }
+        }
+        game
+    }
+}
+
+fn play(red: &mut dyn Strategy, blue: &mut dyn Strategy, game: Game) -> Outcome {
+    Bout::new(red, blue, &|_| {})
+        .start(game)
+        .expect("strategies to finish the game")
+        .outcome()
+        .expect("a finished game to have an outcome")
+}
+
+/// 1 for a win of `Red`, 0 for a draw and -1 for a loss, multiplied by `sign`.
+fn result(outcome: Outcome, sign: f64) -> f64 {
+    sign * outcome.margin().signum() as f64
+}
+
+/// The record of an iteration of tuning.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Iteration {
+    /// The number of the iteration, counting from 1
+    pub iteration: usize,
+    /// The average result of the weights perturbed up against the weights perturbed down, between -1 and 1
+    pub score: f64,
+    /// The weights after the iteration
+    pub weights: Weights,
+}
+
+impl Display for Iteration {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{:>4} {:+.3}", self.iteration, self.score)?;
+        for weight in self.weights.values() {
+            write!(f, " {:.4}", weight)?;
+        }
+        Ok(())
+    }
+}
+
+/// The record of a tuning run.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Tuning {
+    iterations: Vec<Iteration>,
+    weights: Weights,
+}
+
+impl Tuning {
+    /// The iterations, in the order they ran.
+    pub fn iterations(&self) -> &[Iteration] {
+        &self.iterations
+    }
+
+    /// The weights after the last iteration.
+    ///
+    /// These are the weights the tuning arrived at, not necessarily the weights of the iteration with the highest
+    /// score. The score of an iteration compares two perturbations, not the weights themselves.
+    pub fn weights(&self) -> &Weights {
+        &self.weights
+    }
+}
+
+impl Display for Tuning {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for iteration in &self.iterations {
+            writeln!(f, "{}", iteration)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::MAX_BOWLS;
+    use crate::strategy::heuristic::{delta, extra_turns, mobility};
+
+    fn features() -> Weighted {
+        Weighted::new()
+            .with(1.0, delta())
+            .with(0.25, extra_turns())
+            .with(0.0, mobility())
+    }
+
+    fn spsa(seed: u64) -> Spsa<fn() -> Weighted> {
+        Spsa::tuning(features as fn() -> Weighted)
+            .iterations(3)
+            .games(2)
+            .limited_to(Depth::Limit(2))
+            .on(GameBuilder::new().bowls(4).stones(3))
+            .seeded(seed)
+            .build()
+            .expect("a playable game")
+    }
+
+    #[test]
+    fn tuning_records_every_iteration() {
+        let tuning = spsa(3).tune();
+
+        assert_eq!(tuning.iterations().len(), 3);
+        assert_eq!(tuning.iterations()[2].weights, *tuning.weights());
+        assert_eq!(tuning.weights().values().len(), 3);
+        assert!(tuning
+            .iterations()
+            .iter()
+            .all(|iteration| iteration.score.abs() <= 1.0));
+    }
+
+    #[test]
+    fn seeded_tuning_can_be_repeated() {
+        assert_eq!(spsa(11).tune(), spsa(11).tune());
+    }
+
+    #[test]
+    fn tuning_starts_at_given_weights() {
+        let spsa = Spsa::tuning(features)
+            .starting_at(Weights::new(vec![2.0, 0.5]))
+            .build()
+            .expect("a playable game");
+
+        assert_eq!(spsa.weights(), Weights::new(vec![2.0, 0.5, 0.0]));
+    }
+
+    #[test]
+    fn weights_move_with_the_winner() {
+        // Starting from a neutral weight, the perturbation that values captured stones positively wins, whichever
+        // direction it was perturbed in. So the weight grows.
+        let mut spsa = Spsa::tuning(|| Weighted::new().with(0.0, delta()))
+            .games(4)
+            .limited_to(Depth::Limit(2))
+            .on(GameBuilder::new().bowls(4).stones(3))
+            .seeded(5)
+            .build()
+            .expect("a playable game");
+
+        let iteration = spsa.step();
+
+        assert_ne!(iteration.score, 0.0);
+        assert!(iteration.weights.values()[0] > 0.0);
+    }
+
+    #[test]
+    fn unplayable_setups_are_rejected() {
+        let result = Spsa::tuning(features)
+            .on(GameBuilder::new().bowls(MAX_BOWLS + 1))
+            .build();
+
+        assert!(matches!(result, Err(BadSetup::TooManyBowls)));
+    }
+}
@@ -0,0 +1,107 @@
+//! A file format for weight vectors.
+//!
+//! A weights file has one weight per line, in the order the heuristics were added to the `Weighted` sum. Empty lines
+//! and lines starting with `#` are ignored.
+//!
+//! ```text
+//! # tuned by spsa
+//! 1
+//! 0.25
+//! -0.125
+//! ```
+
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// The weights of a `Weighted` sum of heuristics.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Weights {
+    values: Vec<f64>,
+}
+
+/// Discriminates between all the ways a weights file can be rejected.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum BadWeights {
+    /// The line with this number, counting from 1, is not a finite number.
+    MalformedWeight(usize),
+}
+
+impl Weights {
+    /// Create weights from their values.
+    pub fn new(values: Vec<f64>) -> Self {
+        Weights { values }
+    }
+
+    /// The values of the weights.
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+}
+
+impl From<Vec<f64>> for Weights {
+    fn from(values: Vec<f64>) -> Self {
+        Weights::new(values)
+    }
+}
+
+impl Display for Weights {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for value in &self.values {
+            writeln!(f, "{}", value)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Weights {
+    type Err = BadWeights;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut values = vec![];
+        for (index, line) in input.lines().map(str::trim).enumerate() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let value = line
+                .parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite())
+                .ok_or(BadWeights::MalformedWeight(index + 1))?;
+            values.push(value);
+        }
+        Ok(Weights { values })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weights_survive_a_round_trip() {
+        let weights = Weights::new(vec![1.0, 0.25, -0.1, 1e-7]);
+
+        let read = weights.to_string().parse::<Weights>();
+
+        assert_eq!(read, Ok(weights));
+    }
+
+    #[test]
+    fn comments_and_empty_lines_are_ignored() {
+        let read = "# tuned\n1\n\n  0.5 \n".parse::<Weights>();
+
+        assert_eq!(read, Ok(Weights::new(vec![1.0, 0.5])));
+    }
+
+    #[test]
+    fn malformed_weights_are_reported_by_line() {
+        assert_eq!(
+            "1\nheavy\n".parse::<Weights>(),
+            Err(BadWeights::MalformedWeight(2))
+        );
+        assert_eq!(
+            "inf\n".parse::<Weights>(),
+            Err(BadWeights::MalformedWeight(1))
+        );
+    }
+}
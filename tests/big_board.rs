@@ -0,0 +1,22 @@
+extern crate mancala;
+
+use mancala::bout::Bout;
+use mancala::game::GameBuilder;
+use mancala::strategy::tree::Depth;
+use mancala::strategy::{AlphaBeta, First};
+
+#[test]
+fn strategies_play_on_big_boards() {
+    let mut red_strategy = AlphaBeta::strategy().limited_to(Depth::Limit(3)).build();
+    let mut blue_strategy = First::new();
+    let mut bout = Bout::new(&mut red_strategy, &mut blue_strategy, &|_| {});
+
+    let game = GameBuilder::new()
+        .bowls(12)
+        .stones(12)
+        .build()
+        .expect("a playable game");
+    let result = bout.start(game).expect("a finished game with score");
+
+    assert!(result.score().is_some());
+}
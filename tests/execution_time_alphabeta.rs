@@ -16,7 +16,11 @@ fn calculating_end_games_should_be_quick() {
 
     let start_time = SystemTime::now();
     for stones in 1..15 {
-        let game = GameBuilder::new().bowls(2).stones(stones).build();
+        let game = GameBuilder::new()
+            .bowls(2)
+            .stones(stones)
+            .build()
+            .expect("a playable game");
         let _result = bout.start(game).expect("a finished game with score");
     }
     let finish_time = SystemTime::now();
@@ -0,0 +1,32 @@
+extern crate mancala;
+
+use mancala::game::Position;
+use mancala::strategy::tree::ordering::InOrder;
+use mancala::strategy::tree::{AlphaBeta, Depth, DepthLimitedSearch, MinMax};
+
+#[test]
+fn alpha_beta_agrees_with_minmax_at_every_depth() {
+    let positions = vec![
+        Position::new(3, 3),
+        Position::new(4, 2),
+        Position::from([1, 2, 1, 0, 2, 1]),
+        Position::from((3, 1, [0, 2, 3, 1, 0, 4])),
+    ];
+    for position in positions {
+        for depth in 1..7 {
+            let search_depth = Depth::Limit(depth);
+            let (_, expected) = MinMax::new().search(&position, &search_depth);
+
+            let mut prioritized = AlphaBeta::strategy().build();
+            let (_, value) = prioritized.search(&position, &search_depth);
+            assert_eq!(value, expected, "prioritized, depth {}", depth);
+
+            let mut in_order = AlphaBeta::strategy()
+                .ordered_by(InOrder {})
+                .with_table_size(0)
+                .build();
+            let (_, value) = in_order.search(&position, &search_depth);
+            assert_eq!(value, expected, "in order, depth {}", depth);
+        }
+    }
+}
@@ -0,0 +1,42 @@
+extern crate mancala;
+
+use mancala::bout::Bout;
+use mancala::game::{GameBuilder, Oware, Position};
+use mancala::strategy::tree::Depth;
+use mancala::strategy::{AlphaBeta, MinMax, Random, Strategy};
+use rand::thread_rng;
+
+#[test]
+fn alpha_beta_plays_oware() {
+    let mut red_strategy = AlphaBeta::strategy().limited_to(Depth::Limit(4)).build();
+    let mut blue_strategy = AlphaBeta::strategy().limited_to(Depth::Limit(2)).build();
+    let mut bout = Bout::new(&mut red_strategy, &mut blue_strategy, &|_| {});
+
+    let game = GameBuilder::new()
+        .rules(Oware::new())
+        .build()
+        .expect("a playable game");
+    let result = bout.start(game).expect("a finished game with score");
+
+    assert!(result.score().is_some());
+}
+
+#[test]
+fn min_max_plays_oware() {
+    let position = Position::from((3, 0, [0, 0, 1, 1, 1, 1])).with_rules(Oware::new());
+    let mut strategy = MinMax::new();
+
+    let bowl = strategy.play(&position);
+
+    assert_eq!(bowl, Some(2));
+}
+
+#[test]
+fn random_plays_legal_oware_bowls() {
+    let position = Position::from([3, 1, 0, 0, 0, 0]).with_rules(Oware::new());
+    let mut strategy = Random::new(thread_rng());
+
+    let bowl = strategy.play(&position);
+
+    assert_eq!(bowl, Some(0));
+}